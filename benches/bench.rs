@@ -0,0 +1,137 @@
+// These benchmarks use a small timing loop instead of the unstable `test`
+// crate, so that they run on any toolchain with `cargo bench`. Pass a name
+// to only run the benchmarks that contain it, e.g., `cargo bench -- large`.
+
+extern crate tabwriter;
+
+use std::env;
+use std::io::Write;
+use std::mem;
+use std::ptr;
+use std::str;
+use std::time::{Duration, Instant};
+
+use tabwriter::TabWriter;
+
+/// How long each benchmark is run for, after warming up.
+const MEASURE: Duration = Duration::from_secs(1);
+
+/// How many times the small table is flushed by `flush_repeatedly`.
+const FLUSHES: usize = 100;
+
+fn main() {
+    let filter = env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let run = |name: &str, input: &[u8], f: fn(&[u8])| {
+        let selected = match filter {
+            None => true,
+            Some(ref filter) => name.contains(&**filter),
+        };
+        if selected {
+            bench(name, input, f);
+        }
+    };
+
+    let small = table(10, 3, "cell");
+    let large = table(10_000, 5, "cell");
+    let unicode = table(1_000, 5, "你好wörld");
+    let passthrough = "no tabs on this line at all\n".repeat(10_000);
+
+    run("small_table", small.as_bytes(), write_once);
+    run("small_table_flushes", small.repeat(FLUSHES).as_bytes(),
+        flush_repeatedly);
+    run("large_table", large.as_bytes(), write_once);
+    run("unicode_cells", unicode.as_bytes(), write_once);
+    run("many_small_writes", large.as_bytes(), write_in_chunks);
+    run("one_large_write", large.as_bytes(), write_once);
+    run("passthrough", passthrough.as_bytes(), write_once);
+    run("pipe_small_table", small.as_bytes(), pipe);
+    run("write_fmt_rows", large.as_bytes(), write_fmt_rows);
+}
+
+/// Returns a table with cells of different widths in every column.
+fn table(rows: usize, cols: usize, cell: &str) -> String {
+    let mut text = String::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            if col > 0 {
+                text.push('\t');
+            }
+            text.push_str(&cell.repeat(1 + (row + col) % 4));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn write_once(input: &[u8]) {
+    let mut tw = TabWriter::new(Vec::new());
+    tw.write_all(input).unwrap();
+    tw.flush().unwrap();
+    black_box(tw.into_inner().unwrap());
+}
+
+fn write_in_chunks(input: &[u8]) {
+    let mut tw = TabWriter::new(Vec::new());
+    for chunk in input.chunks(8) {
+        tw.write_all(chunk).unwrap();
+    }
+    tw.flush().unwrap();
+    black_box(tw.into_inner().unwrap());
+}
+
+/// Writes every line with `write!`, one cell argument at a time.
+fn write_fmt_rows(input: &[u8]) {
+    let mut tw = TabWriter::new(Vec::new());
+    for line in str::from_utf8(input).unwrap().lines() {
+        for (i, cell) in line.split('\t').enumerate() {
+            let sep = if i == 0 { "" } else { "\t" };
+            write!(tw, "{}{}", sep, cell).unwrap();
+        }
+        writeln!(tw).unwrap();
+    }
+    tw.flush().unwrap();
+    black_box(tw.into_inner().unwrap());
+}
+
+fn pipe(input: &[u8]) {
+    black_box(TabWriter::pipe(str::from_utf8(input).unwrap()));
+}
+
+/// Splits `input` into `FLUSHES` tables, and writes and flushes each of
+/// them with the same writer.
+fn flush_repeatedly(input: &[u8]) {
+    let mut tw = TabWriter::new(Vec::new());
+    for table in input.chunks(input.len() / FLUSHES) {
+        tw.write_all(table).unwrap();
+        tw.flush().unwrap();
+        tw.get_mut().clear();
+    }
+    black_box(tw.into_inner().unwrap());
+}
+
+/// Runs `f` on `input` repeatedly and reports the time per iteration and
+/// the throughput in terms of input bytes.
+fn bench(name: &str, input: &[u8], f: fn(&[u8])) {
+    f(black_box(input));
+    let start = Instant::now();
+    let mut iters = 0u32;
+    while start.elapsed() < MEASURE {
+        f(black_box(input));
+        iters += 1;
+    }
+    let per_iter = start.elapsed() / iters;
+    let secs = per_iter.as_secs_f64();
+    println!("{:<20} {:>12?}/iter {:>10.1} MB/s",
+             name, per_iter, input.len() as f64 / secs / 1_000_000.0);
+}
+
+/// Hides `x` from the optimizer, like `std::hint::black_box`, which is too
+/// new for our minimum Rust version.
+fn black_box<T>(x: T) -> T {
+    // The optimizer must assume that a volatile read may return anything.
+    unsafe {
+        let y = ptr::read_volatile(&x);
+        mem::forget(x);
+        y
+    }
+}
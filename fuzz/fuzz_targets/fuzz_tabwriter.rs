@@ -0,0 +1,37 @@
+#![no_main]
+
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate tabwriter;
+
+use std::io::Write;
+use std::str;
+
+use tabwriter::TabWriter;
+
+// The first byte of the input picks where the rest of it is split into two
+// writes, so that cells, lines and UTF-8 sequences get split between calls
+// to `write`.
+fuzz_target!(|data: &[u8]| {
+    let (split, text) = match data.split_first() {
+        Some((&split, text)) => (split as usize, text),
+        None => return,
+    };
+    let split = split % (text.len() + 1);
+
+    let mut tw = TabWriter::new(vec![]).padding(1);
+    tw.write_all(&text[..split]).unwrap();
+    tw.write_all(&text[split..]).unwrap();
+    tw.flush().unwrap();
+    let out = tw.into_inner().unwrap();
+
+    if str::from_utf8(text).is_ok() {
+        assert!(str::from_utf8(&out).is_ok(), "output is not UTF-8");
+    }
+
+    // Every cell is padded to at most the width of the widest cell, plus
+    // padding, and the width of a cell never exceeds its length in bytes.
+    let cells = text.iter().filter(|&&b| b == b'\t' || b == b'\n').count();
+    let bound = text.len() + (cells + 1) * (text.len() + 2);
+    assert!(out.len() <= bound, "{} bytes of output", out.len());
+});
@@ -8,9 +8,22 @@
 //! This package is also bundled with a program, `tabwriter`,
 //! that exposes this functionality at the command line.
 //!
-//! Here's an example that shows basic alignment:
+//! The simplest way to align text is `TabWriter::pipe`, which aligns a string
+//! with the default settings:
 //!
 //! ```rust
+//! # #[cfg(feature = "std")] {
+//! use tabwriter::TabWriter;
+//!
+//! let aligned = TabWriter::pipe("name\tcount\napples\t3\n");
+//! assert_eq!(aligned, "name    count\napples  3\n");
+//! # }
+//! ```
+//!
+//! Here's an example that shows basic alignment with a writer:
+//!
+//! ```rust
+//! # #[cfg(feature = "std")] {
 //! use std::io::Write;
 //! use tabwriter::TabWriter;
 //!
@@ -30,6 +43,7 @@
 //! Metallica          Black
 //! The Boss           Darkness on the Edge of Town
 //! ");
+//! # }
 //! ```
 //!
 //! Note that `flush` **must** be called or else `TabWriter` may never write
@@ -41,6 +55,7 @@
 //! are aligned:
 //!
 //! ```rust
+//! # #[cfg(feature = "std")] {
 //! use std::io::Write;
 //! use tabwriter::TabWriter;
 //!
@@ -68,322 +83,60 @@
 //!    y += 2 * 2; // that is separately aligned
 //!}
 //!");
+//! # }
+//! ```
+//!
+//! When the aligned text is wanted as a `String`, `TabWriter::new_string`
+//! and `into_string` take care of flushing and converting the output:
+//!
+//! ```rust
+//! # #[cfg(feature = "std")] {
+//! use std::io::Write;
+//! use tabwriter::TabWriter;
+//!
+//! let mut tw = TabWriter::new_string();
+//! write!(&mut tw, "a\tb\nxx\tyy\n").unwrap();
+//! assert_eq!(tw.into_string().unwrap(), "a   b\nxx  yy\n");
+//! # }
 //! ```
 
 #![deny(missing_docs)]
-
-#[cfg(feature = "ansi_formatting")]
-#[macro_use]
-extern crate lazy_static;
-#[cfg(feature = "ansi_formatting")]
-extern crate regex;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
+// Without `std`, `core` is always in scope.
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "futures-io")]
+extern crate futures_io;
+extern crate memchr;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "unicode-width")]
 extern crate unicode_width;
 
-use std::cmp;
-use std::error;
-use std::fmt;
-use std::io::{self, Write};
-use std::iter;
-use std::mem;
-use std::str;
-
-#[cfg(feature = "ansi_formatting")]
-use regex::Regex;
-#[cfg(feature = "ansi_formatting")]
-use std::borrow::Cow;
-
-#[cfg(test)]
+#[cfg(feature = "std")]
+pub use writer::{
+    align, align_with_opts, column_widths_from_str, compute_widths,
+    AlignedCell, Alignment, AlignmentResult, BoxStyle, BoxTableWriter, Column,
+    CsvTableWriter, GroupSeparator, HtmlTableWriter, IntoInnerError, IntoRows,
+    IntoStringError, LatexTabularWriter, LineEnding, MarkdownTableWriter,
+    RowSeparator, RstTableWriter, TabAligner, TabReader, TabWriter,
+    TabWriterBuilder,
+};
+#[cfg(feature = "serde")]
+pub use writer::{SerializeError, TableMap, TableSerializer};
+pub use widths::{cell_widths, display_columns, Cell};
+
+#[cfg(all(test, feature = "std"))]
 mod test;
-
-/// TabWriter wraps an arbitrary writer and aligns tabbed output.
-///
-/// Elastic tabstops work by aligning *contiguous* tabbed delimited fields
-/// known as *column blocks*. When a line appears that breaks all contiguous
-/// blocks, all buffered output will be flushed to the underlying writer.
-/// Otherwise, output will stay buffered until `flush` is explicitly called.
-#[derive(Debug)]
-pub struct TabWriter<W> {
-    w: W,
-    buf: io::Cursor<Vec<u8>>,
-    lines: Vec<Vec<Cell>>,
-    curcell: Cell,
-    minwidth: usize,
-    padding: usize,
-}
-
-#[derive(Debug)]
-struct Cell {
-    start: usize, // offset into TabWriter.buf
-    width: usize, // in characters
-    size: usize,  // in bytes
-}
-
-impl<W: io::Write> TabWriter<W> {
-    /// Create a new `TabWriter` from an existing `Writer`.
-    ///
-    /// All output written to `Writer` is passed through `TabWriter`.
-    /// Contiguous column blocks indicated by tabs are aligned.
-    ///
-    /// Note that `flush` must be called to guarantee that `TabWriter` will
-    /// write to the given writer.
-    pub fn new(w: W) -> TabWriter<W> {
-        TabWriter {
-            w: w,
-            buf: io::Cursor::new(Vec::with_capacity(1024)),
-            lines: vec!(vec!()),
-            curcell: Cell::new(0),
-            minwidth: 2,
-            padding: 2,
-        }
-    }
-
-    /// Set the minimum width of each column. That is, all columns will have
-    /// *at least* the size given here. If a column is smaller than `minwidth`,
-    /// then it is passed with spaces.
-    ///
-    /// The default minimum width is `2`.
-    pub fn minwidth(mut self, minwidth: usize) -> TabWriter<W> {
-        self.minwidth = minwidth;
-        self
-    }
-
-    /// Set the padding between columns. All columns will be separated by
-    /// *at least* the number of spaces indicated by `padding`. If `padding`
-    /// is zero, then columns may run up against each other without any
-    /// separation.
-    ///
-    /// The default padding is `2`.
-    pub fn padding(mut self, padding: usize) -> TabWriter<W> {
-        self.padding = padding;
-        self
-    }
-
-    /// Unwraps this `TabWriter`, returning the underlying writer.
-    ///
-    /// This internal buffer is flushed before returning the writer. If the
-    /// flush fails, then an error is returned.
-    pub fn into_inner(mut self) -> Result<W, IntoInnerError<TabWriter<W>>> {
-        match self.flush() {
-            Ok(()) => Ok(self.w),
-            Err(err) => Err(IntoInnerError(self, err)),
-        }
-    }
-
-    /// Resets the state of the aligner. Once the aligner is reset, all future
-    /// writes will start producing a new alignment.
-    fn reset(&mut self) {
-        self.buf = io::Cursor::new(Vec::with_capacity(1024));
-        self.lines = vec!(vec!());
-        self.curcell = Cell::new(0);
-    }
-
-    /// Adds the bytes received into the buffer and updates the size of
-    /// the current cell.
-    fn add_bytes(&mut self, bytes: &[u8]) {
-        self.curcell.size += bytes.len();
-        let _ = self.buf.write_all(bytes); // cannot fail
-    }
-
-    /// Ends the current cell, updates the UTF8 width of the cell and starts
-    /// a fresh cell.
-    fn term_curcell(&mut self) {
-        let mut curcell = Cell::new(self.buf.position() as usize);
-        mem::swap(&mut self.curcell, &mut curcell);
-
-        curcell.update_width(&self.buf.get_ref());
-        self.curline_mut().push(curcell);
-    }
-
-    /// Return a view of the current line of cells.
-    fn curline(&mut self) -> &[Cell] {
-        let i = self.lines.len() - 1;
-        &*self.lines[i]
-    }
-
-    /// Return a mutable view of the current line of cells.
-    fn curline_mut(&mut self) -> &mut Vec<Cell> {
-        let i = self.lines.len() - 1;
-        &mut self.lines[i]
-    }
-}
-
-impl Cell {
-    fn new(start: usize) -> Cell {
-        Cell { start: start, width: 0, size: 0 }
-    }
-
-    fn update_width(&mut self, buf: &[u8]) {
-        let end = self.start + self.size;
-        self.width = display_columns(&buf[self.start..end]);
-    }
-}
-
-impl<W: io::Write> io::Write for TabWriter<W> {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        let mut lastterm = 0usize;
-        for (i, &c) in buf.iter().enumerate() {
-            match c {
-                b'\t' | b'\n' => {
-                    self.add_bytes(&buf[lastterm..i]);
-                    self.term_curcell();
-                    lastterm = i + 1;
-                    if c == b'\n' {
-                        let ncells = self.curline().len();
-                        self.lines.push(vec!());
-                        // Having a single cell means that *all* previous
-                        // columns have been broken, so we should just flush.
-                        if ncells == 1 {
-                            try!(self.flush());
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        self.add_bytes(&buf[lastterm..]);
-        Ok(buf.len())
-    }
-
-    fn flush(&mut self) -> io::Result<()> {
-        if self.curcell.size > 0 {
-            self.term_curcell();
-        }
-        let widths = cell_widths(&self.lines, self.minwidth);
-
-        // This is a trick to avoid allocating padding for every cell.
-        // Just allocate the most we'll ever need and borrow from it.
-        let biggest_width = widths.iter()
-                                  .map(|ws| ws.iter().map(|&w|w).max()
-                                              .unwrap_or(0))
-                                  .max().unwrap_or(0);
-        let padding: String =
-            iter::repeat(' ').take(biggest_width + self.padding).collect();
-
-        let mut first = true;
-        for (line, widths) in self.lines.iter().zip(widths.iter()) {
-            if !first { try!(self.w.write_all(b"\n")); } else { first = false }
-            for (i, cell) in line.iter().enumerate() {
-                let bytes = &self.buf.get_ref()[cell.start..cell.start + cell.size];
-                try!(self.w.write_all(bytes));
-                if i >= widths.len() {
-                    assert_eq!(i, line.len()-1);
-                } else {
-                    assert!(widths[i] >= cell.width);
-                    let padsize = self.padding + widths[i] - cell.width;
-                    try!(write!(&mut self.w, "{}", &padding[0..padsize]));
-                }
-            }
-        }
-
-        self.reset();
-        Ok(())
-    }
-}
-
-/// An error returned by `into_inner`.
-///
-/// This combines the error that happened while flushing the buffer with the
-/// `TabWriter` itself.
-pub struct IntoInnerError<W>(W, io::Error);
-
-impl<W> IntoInnerError<W> {
-    /// Returns the error which caused the `into_error()` call to fail.
-    pub fn error(&self) -> &io::Error {
-        &self.1
-    }
-
-    /// Returns the `TabWriter` instance which generated the error.
-    pub fn into_inner(self) -> W {
-        self.0
-    }
-}
-
-impl<W> fmt::Debug for IntoInnerError<W> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.error().fmt(f)
-    }
-}
-
-impl<W> fmt::Display for IntoInnerError<W> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.error().fmt(f)
-    }
-}
-
-impl<W: ::std::any::Any> error::Error for IntoInnerError<W> {
-    fn description(&self) -> &str {
-        self.error().description()
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
-        Some(self.error())
-    }
-}
-
-fn cell_widths(lines: &Vec<Vec<Cell>>, minwidth: usize) -> Vec<Vec<usize>> {
-    // Naively, this algorithm looks like it could be O(n^2m) where `n` is
-    // the number of lines and `m` is the number of contiguous columns.
-    //
-    // However, I claim that it is actually O(nm). That is, the width for
-    // every contiguous column is computed exactly once.
-    let mut ws: Vec<_> = (0..lines.len()).map(|_| vec![]).collect();
-    for (i, iline) in lines.iter().enumerate() {
-        if iline.is_empty() {
-            continue
-        }
-        for col in ws[i].len()..(iline.len()-1) {
-            let mut width = minwidth;
-            let mut contig_count = 0;
-            for line in lines[i..].iter() {
-                if col + 1 >= line.len() { // ignores last column
-                    break
-                }
-                contig_count += 1;
-                width = cmp::max(width, line[col].width);
-            }
-            assert!(contig_count >= 1);
-            for j in i..(i+contig_count) {
-                ws[j].push(width);
-            }
-        }
-    }
-    ws
-}
-
-#[cfg(not(feature = "ansi_formatting"))]
-fn display_columns(bytes: &[u8]) -> usize {
-    use unicode_width::UnicodeWidthChar;
-
-    // If we have a Unicode string, then attempt to guess the number of
-    // *display* columns used.
-    match str::from_utf8(bytes) {
-        Err(_) => bytes.len(),
-        Ok(s) => s.chars()
-                  .map(|c| UnicodeWidthChar::width(c).unwrap_or(0))
-                  .fold(0, |sum, width| sum + width),
-    }
-}
-
-#[cfg(feature = "ansi_formatting")]
-fn display_columns(bytes: &[u8]) -> usize {
-    use unicode_width::UnicodeWidthChar;
-
-    // If we have a Unicode string, then attempt to guess the number of
-    // *display* columns used.
-    match str::from_utf8(bytes) {
-        Err(_) => bytes.len(),
-        Ok(s) => strip_formatting(s).chars()
-                    .map(|c| UnicodeWidthChar::width(c).unwrap_or(0))
-                    .fold(0, |sum, width| sum + width),
-    }
-}
-
-#[cfg(feature = "ansi_formatting")]
-fn strip_formatting<'t>(input: &'t str) -> Cow<'t, str> {
-    // use lazy_static to avoid compiling the regex every time
-    // this function is called
-    lazy_static! {
-        static ref RE: regex::Regex = Regex::new(r#"\x1B\[.+?m"#).unwrap();
-    }
-    RE.replace_all(input, "")
-}
+#[cfg(test)]
+mod test_widths;
+mod widths;
+#[cfg(feature = "std")]
+mod writer;
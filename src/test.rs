@@ -1,5 +1,9 @@
 use std::io::Write;
-use TabWriter;
+use {align, align_with_opts, cell_widths, column_widths_from_str,
+     compute_widths, Alignment, BoxStyle, BoxTableWriter, Cell, Column,
+     CsvTableWriter, GroupSeparator, HtmlTableWriter, LatexTabularWriter,
+     LineEnding, MarkdownTableWriter, RowSeparator, RstTableWriter, TabAligner,
+     TabReader, TabWriter, TabWriterBuilder};
 
 
 fn ordie<T, E: ToString>(r: Result<T, E>) -> T {
@@ -81,11 +85,47 @@ fn test_empty_cells_ignore_trailing() {
     iseq(tabw().padding(0).minwidth(2), "\t\t\t", "    ");
 }
 
+#[test]
+fn test_leading_tab() {
+    iseq(tabw(), "\tfoo\nbar\tbaz", "     foo\nbar  baz");
+}
+
+#[test]
+fn test_leading_tab_minwidth() {
+    iseq(tabw(), "\tfoo\n\tbar", "    foo\n    bar");
+}
+
+#[test]
+fn test_consecutive_tabs() {
+    iseq(tabw(), "a\t\tb\nxxx\tyyy\tzzz", "a         b\nxxx  yyy  zzz");
+}
+
+#[test]
+fn test_consecutive_tabs_minwidth() {
+    iseq(tabw(), "a\t\tb\nxxx\t\tzzz", "a        b\nxxx      zzz");
+}
+
 #[test]
 fn test_one_cell() {
     iseq(tabw().padding(2).minwidth(2), "a\tb\nxx\tyy", "a   b\nxx  yy");
 }
 
+#[test]
+fn test_no_trailing_newline_separate_writes() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\n"));
+    ordie(tw.write_all(b"xxx\tyyy"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a    b\nxxx  yyy");
+}
+
+// Like `test_empty_cells_ignore_trailing`, a final tab without a newline
+// doesn't start an empty cell.
+#[test]
+fn test_trailing_tab_no_newline() {
+    iseq(tabw(), "a\tb\nxxx\tyyy\t", "a    b\nxxx  yyy");
+}
+
 #[test]
 fn test_no_padding() {
     iseq(tabw().padding(0).minwidth(2), "a\tb\nxx\tyy", "a b\nxxyy");
@@ -104,6 +144,19 @@ fn test_contiguous_columns() {
          "x foo    x\nx foofoo x\n\nx foofoofoo x");
 }
 
+#[test]
+fn test_ragged_rows() {
+    // The first two columns line up, but the last cell of every line is
+    // written at its natural width, whichever column it is in.
+    iseq(tabw().padding(1).minwidth(0),
+         "a\tb\tc\n1\t2\n", "a b c\n1 2\n");
+    iseq(tabw().padding(1).minwidth(0),
+         "aaa\tb\tc\n1\t2222\n", "aaa b c\n1   2222\n");
+    iseq(tabw().padding(1).minwidth(0),
+         "a\tb\n1\t2\t3333\nxx\tyy\tz\n",
+         "a  b\n1  2  3333\nxx yy z\n");
+}
+
 #[test]
 fn test_unicode() {
     iseq(tabw().padding(2).minwidth(2),
@@ -113,6 +166,36 @@ fn test_unicode() {
           a     Bâb el Ahmar   z")
 }
 
+#[test]
+fn test_zero_length_writes() {
+    let mut tw = tabw();
+    assert_eq!(ordie(tw.write(&[])), 0);
+    ordie(tw.write_all(b"a\tb\r"));
+    assert_eq!(ordie(tw.write(&[])), 0);
+    ordie(tw.write_all(b"\nxx"));
+    assert_eq!(ordie(tw.write(&[])), 0);
+    ordie(tw.write_all(b"\tyy\n"));
+    assert_eq!(tw.buffered_bytes(), 6);
+    assert_eq!(ordie(tw.into_string()), tabify(tabw(), "a\tb\nxx\tyy\n"));
+
+    let mut tw = tabw();
+    assert_eq!(ordie(tw.write(&[])), 0);
+    ordie(tw.flush());
+    assert!(tw.get_ref().is_empty());
+}
+
+#[test]
+fn test_unicode_split_writes() {
+    // Every multi-byte character is split across writes, but widths are
+    // only computed once a cell has ended.
+    let mut tw = tabw();
+    for b in "Þykkvibær\tz\nBâb\tzz\n".bytes() {
+        ordie(tw.write_all(&[b]));
+    }
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), "Þykkvibær  z\nBâb        zz\n".as_bytes());
+}
+
 #[test]
 fn test_contiguous_columns_complex() {
     iseq(tabw().padding(1).minwidth(3),
@@ -147,8 +230,1931 @@ fn test_ansi_formatting() {
          \x1b[34mfoo\tbar\tfoobar\n\x1b[0m";
 
     iseq(tabw(),
-         &output[..],
+         output,
          "foo  bar  foobar\n\
          \x1b[31mföÅ\x1b[0m  \x1b[32mbär\x1b[0m  \x1b[36mfoobar\x1b[0m\n\
          \x1b[34mfoo  bar  foobar\n\x1b[0m")
 }
+
+#[test]
+fn test_right_alignment() {
+    iseq(tabw().column_alignment(0, Alignment::Right),
+         "1\t2\n100\t3\n", "  1  2\n100  3\n");
+}
+
+#[test]
+fn test_center_alignment() {
+    iseq(tabw().padding(1).column_alignment(0, Alignment::Center),
+         "a\tx\nabcd\ty\nab\tz\n", " a   x\nabcd y\n ab  z\n");
+}
+
+#[test]
+fn test_mixed_alignment() {
+    iseq(tabw().padding(1).column_alignment(1, Alignment::Right),
+         "a\t1\tx\nabc\t100\ty\n", "a     1 x\nabc 100 y\n");
+}
+
+#[test]
+fn test_fmt_write() {
+    use std::fmt;
+
+    let mut tw = tabw();
+    ordie(fmt::Write::write_str(&mut tw, "a\tb\nxx"));
+    ordie(fmt::Write::write_fmt(&mut tw, format_args!("\t{}\n", "yy")));
+    ordie(tw.flush());
+    let written = ordie(String::from_utf8(tw.into_inner().unwrap()));
+    assert_eq!(written, "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_get_ref_get_mut() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\n"));
+    assert!(tw.get_ref().is_empty());
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a   b\n");
+    tw.get_mut().clear();
+    assert!(tw.get_ref().is_empty());
+}
+
+#[test]
+fn test_reset_discards() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"aaaaaa\tb\n"));
+    tw.reset();
+    ordie(tw.write_all(b"a\tb\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a   b\n");
+}
+
+#[test]
+fn test_crlf_input() {
+    iseq(tabw(), "a\t b\r\n c\t d\r\n", "a    b\n c   d\n");
+}
+
+#[test]
+fn test_crlf_input_trailing_tab() {
+    // The `\r` is removed before the cell ends, so it doesn't become a cell
+    // of its own or count towards a width.
+    iseq(tabw(), "a\tb\t\r\nxx\tyy\tz\r\n", "a   b   \nxx  yy  z\n");
+}
+
+#[test]
+fn test_crlf_input_blank_line() {
+    iseq(tabw(), "a\tb\r\n\r\nxxx\ty\r\n", "a   b\n\nxxx  y\n");
+}
+
+#[test]
+fn test_crlf_input_split() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\r"));
+    ordie(tw.write_all(b"\nxx\tyy\r\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_crlf_output() {
+    iseq(tabw().line_ending(LineEnding::CrLf),
+         "a\tb\nxx\tyy\n", "a   b\r\nxx  yy\r\n");
+}
+
+#[test]
+fn test_crlf_output_roundtrip() {
+    let input = "a\tb\r\nxx\tyy\n\nno tabs\r\nabc\td\n";
+    let written = tabify(tabw().line_ending(LineEnding::CrLf), input);
+    assert_eq!(written, "a   b\r\nxx  yy\r\n\r\nno tabs\r\nabc  d\r\n");
+    assert!(written.split_terminator('\n').all(|line| line.ends_with('\r')));
+}
+
+#[test]
+fn test_line_prefix() {
+    iseq(tabw().line_prefix("  "), "a\tb\txyz\nxxx\tyy\tz\n\tq\tr",
+         "  a    b   xyz\n  xxx  yy  z\n       q   r");
+    iseq(tabw().line_prefix("# ").lookahead(1), "a\tb\n\nxx\tyy\n",
+         "# a   b\n# \n# xx  yy\n");
+}
+
+#[test]
+fn test_row_separator() {
+    let input = "a\tb\nxx\tyy\n\nno tabs\nc\td\n";
+    let tw = || tabw().padding(1);
+    iseq(tw().row_separator(RowSeparator::Newline), input,
+         "a  b\nxx yy\n\nno tabs\nc  d\n");
+    iseq(tw().row_separator(RowSeparator::CrLf), input,
+         "a  b\r\nxx yy\r\n\r\nno tabs\r\nc  d\r\n");
+    iseq(tw().row_separator(RowSeparator::Custom(";".to_string())), input,
+         "a  b;xx yy;;no tabs;c  d;");
+    iseq(tw().row_separator(RowSeparator::None), input,
+         "a  bxx yyno tabsc  d");
+}
+
+#[test]
+fn test_row_separator_lookahead() {
+    let tw = tabw().lookahead(1).row_separator(RowSeparator::None);
+    iseq(tw, "a\tb\nxx\tyy\nzzz\tc\n", "a   bxx   yyzzz  c");
+}
+
+#[test]
+fn test_align() {
+    assert_eq!(align("a\tb\nxx\tyy"), "a   b\nxx  yy");
+    assert_eq!(align_with_opts("a\tb\nxx\tyy", 0, 1), "a  b\nxx yy");
+}
+
+#[test]
+fn test_pipe() {
+    assert_eq!(TabWriter::pipe("a\tb\nxx\tyy"), "a   b\nxx  yy");
+    assert_eq!(TabWriter::pipe(""), "");
+}
+
+#[test]
+fn test_compute_widths() {
+    assert_eq!(compute_widths("", 2), Vec::<Vec<usize>>::new());
+    assert_eq!(compute_widths("a\tb\nxx\tyy", 0), vec![vec![2], vec![2]]);
+    assert_eq!(compute_widths("\nfoo\nbar\nfubar", 2),
+               vec![vec![], vec![], vec![], vec![]]);
+    assert_eq!(compute_widths("a\tb\tc\n\nxx\tyy\n", 2),
+               vec![vec![2, 2], vec![], vec![2]]);
+}
+
+#[test]
+fn test_column_widths_from_str() {
+    assert_eq!(column_widths_from_str("", 2), Vec::<usize>::new());
+    assert_eq!(column_widths_from_str("a\tb\tc\n\nxxxx\tyy\n", 0),
+               vec![4, 1]);
+    assert_eq!(column_widths_from_str("no tabs\n", 2), Vec::<usize>::new());
+}
+
+#[test]
+fn test_compute_widths_matches_output() {
+    let input = "a\tb\tc\naaaa\tbbbbb\tc\nfoo\nxyz\t1\n";
+    let widths = compute_widths(input, 2);
+    let written = tabify(tabw(), input);
+    for (line, widths) in written.lines().zip(widths) {
+        let mut start = 0;
+        for width in widths {
+            start += width + 2;
+            let (cell, rest) = line.split_at(start);
+            assert!(cell.ends_with("  ") && !rest.starts_with(' '));
+        }
+    }
+}
+
+#[test]
+fn test_into_string() {
+    let mut tw = TabWriter::new_string();
+    ordie(tw.write_all(b"a\tb\nxx\tyy"));
+    assert_eq!(ordie(tw.into_string()), "a   b\nxx  yy");
+}
+
+#[test]
+fn test_process() {
+    let tw = TabWriter::new_string().minwidth(0).padding(1);
+    assert_eq!(ordie(tw.process("a\tb\nno tabs\nxx\tyy")),
+               "a b\nno tabs\nxx yy");
+}
+
+#[test]
+fn test_into_string_invalid_utf8() {
+    let mut tw = TabWriter::new_string();
+    ordie(tw.write_all(b"a\t\xff\n"));
+    let err = tw.into_string().unwrap_err();
+    assert!(err.utf8_error().is_some());
+    assert!(err.io_error().is_none());
+}
+
+#[test]
+fn test_delimiter() {
+    iseq(tabw().delimiter(b'|'), "a|b\nxx|yy\n", "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_delimiter_tabs_passthrough() {
+    iseq(tabw().delimiter(b'|'), "a\tb|c\nxx|yy\n", "a\tb  c\nxx  yy\n");
+}
+
+#[test]
+#[should_panic]
+fn test_delimiter_non_ascii() {
+    tabw().delimiter(0xFF);
+}
+
+#[test]
+fn test_delimiters() {
+    iseq(tabw().delimiters(b"\t|"),
+         "a|b\tc\nxx\tyy|zz\n", "a   b   c\nxx  yy  zz\n");
+}
+
+#[test]
+fn test_many_delimiters() {
+    iseq(tabw().delimiters(b"|,;|"),
+         "a|b,c\nxx;yy|zz\n", "a   b   c\nxx  yy  zz\n");
+}
+
+#[test]
+#[should_panic]
+fn test_delimiters_empty() {
+    tabw().delimiters(b"");
+}
+
+#[test]
+#[cfg(feature = "ansi_formatting")]
+fn test_ansi_nested() {
+    iseq(tabw(),
+         "\x1b[1m\x1b[31mab\x1b[0m\x1b[0m\tx\nabc\ty\n",
+         "\x1b[1m\x1b[31mab\x1b[0m\x1b[0m   x\nabc  y\n");
+}
+
+#[test]
+#[cfg(feature = "ansi_formatting")]
+fn test_ansi_split_writes() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"\x1b[3"));
+    ordie(tw.write_all(b"1mab\x1b["));
+    ordie(tw.write_all(b"0m\tx\nabc\ty\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"\x1b[31mab\x1b[0m   x\nabc  y\n");
+}
+
+#[test]
+#[cfg(feature = "ansi_formatting")]
+fn test_ansi_non_sgr() {
+    iseq(tabw(),
+         "\x1b[2Kab\tx\nabc\ty\n",
+         "\x1b[2Kab   x\nabc  y\n");
+}
+
+#[test]
+#[cfg(feature = "ansi_formatting")]
+fn test_ansi_incomplete() {
+    iseq(tabw(), "ab\x1b[31\tx\nabc\ty\n", "ab\x1b[31   x\nabc  y\n");
+}
+
+#[test]
+#[cfg(feature = "unicode-width")]
+fn test_east_asian_width() {
+    iseq(tabw().padding(1),
+         "中文\tx\n日本語\ty\n한국어\tz\nab\tw\n",
+         "中文   x\n日本語 y\n한국어 z\nab     w\n");
+}
+
+#[test]
+#[cfg(feature = "unicode-width")]
+fn test_fullwidth_forms() {
+    iseq(tabw().padding(1), "ＡＢ\tx\nabc\ty\n", "ＡＢ x\nabc  y\n");
+}
+
+#[test]
+fn test_arabic() {
+    iseq(tabw().padding(1), "مرحبا\tx\nab\ty\n", "مرحبا x\nab    y\n");
+}
+
+#[test]
+#[cfg(feature = "unicode-width")]
+fn test_combining_characters() {
+    // `e` followed by U+0301 COMBINING ACUTE ACCENT.
+    iseq(tabw().padding(1),
+         "cafe\u{301}\tx\nab\ty\n", "cafe\u{301} x\nab   y\n");
+}
+
+#[test]
+#[cfg(feature = "unicode-width")]
+fn test_hangul_jamo() {
+    // A decomposed syllable: a leading consonant followed by a vowel.
+    iseq(tabw().padding(1),
+         "\u{1100}\u{1161}\tx\nabc\ty\n", "\u{1100}\u{1161}  x\nabc y\n");
+}
+
+#[test]
+#[cfg(feature = "unicode-width")]
+fn test_variation_selector() {
+    // U+2764 HEAVY BLACK HEART followed by U+FE0F VARIATION SELECTOR-16.
+    iseq(tabw().padding(1),
+         "\u{2764}\u{fe0f}\tx\nabc\ty\n", "\u{2764}\u{fe0f}   x\nabc y\n");
+}
+
+#[test]
+fn test_column_minwidth() {
+    iseq(tabw().padding(1).minwidth(0).column_minwidth(1, 5),
+         "a\tb\tc\nxx\tyy\tzz\n", "a  b     c\nxx yy    zz\n");
+}
+
+#[test]
+fn test_column_padding() {
+    iseq(tabw().padding(1).column_padding(0, 4),
+         "a\tb\tc\nxx\tyy\tzz\n", "a     b  c\nxx    yy zz\n");
+}
+
+#[test]
+fn test_column_padding_and_minwidth() {
+    iseq(tabw().minwidth(0).padding(0)
+               .column_minwidth(0, 3).column_padding(0, 2),
+         "a\tb\nxx\tyy\n", "a    b\nxx   yy\n");
+}
+
+#[test]
+fn test_max_column_width() {
+    iseq(tabw().padding(1).max_column_width(5),
+         "abcdefgh\tx\nab\ty\n", "ab... x\nab    y\n");
+}
+
+#[test]
+fn test_max_column_width_marker() {
+    iseq(tabw().padding(1).max_column_width(4).truncation_marker("~"),
+         "abcdef\tx\nab\tabcdef\n", "abc~ x\nab   abc~\n");
+}
+
+#[test]
+fn test_max_column_width_multibyte() {
+    iseq(tabw().padding(1).max_column_width(4).truncation_marker("…"),
+         "Þykkvibær\tx\n", "Þyk… x\n");
+}
+
+#[test]
+#[cfg(feature = "unicode-width")]
+fn test_max_column_width_wide() {
+    // A wide character that does not fit is dropped entirely, so the cell
+    // may end up narrower than the maximum.
+    iseq(tabw().padding(1).max_column_width(6),
+         "日本語日本\tx\nabcdef\ty\n", "日...  x\nabcdef y\n");
+}
+
+#[test]
+fn test_terminal_width_fits() {
+    iseq(tabw().terminal_width(10), "a\tb\nxx\tyy\n", "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_terminal_width_last_cell() {
+    iseq(tabw().terminal_width(20),
+         "name\tdescription\nfoo\ta very long description of foo\n",
+         "name  description\nfoo   a very long...\n");
+}
+
+#[test]
+fn test_terminal_width_columns() {
+    // Both wide columns are narrowed to the same width, so the lines stay
+    // aligned.
+    iseq(tabw().terminal_width(16).truncation_marker("~"),
+         "aaaaaaaaaa\tbbbbbbbbbb\tc\nx\ty\tz\n",
+         "aaaa~  bbbb~  c\nx      y      z\n");
+}
+
+#[test]
+fn test_terminal_width_separator() {
+    iseq(tabw().padding(1).column_separator("|").terminal_width(12)
+               .truncation_marker(""),
+         "abcdefgh\tabcdefgh\n", "abcd | abcde\n");
+}
+
+#[test]
+fn test_builder_reuse() {
+    let builder = TabWriterBuilder::new()
+        .padding(0)
+        .column_alignment(0, Alignment::Right);
+    let first = tabify(builder.build(Vec::new()), "1\tx\n100\ty\n");
+    let second = tabify(builder.build(Vec::new()), "10\tx\n1\ty\n");
+    assert_eq!(first, "  1x\n100y\n");
+    assert_eq!(second, "10x\n 1y\n");
+}
+
+#[test]
+fn test_fill_char() {
+    iseq(tabw().fill_char('.'), "a\tb\nxx\tyy\n", "a...b\nxx..yy\n");
+    iseq(tabw().fill_char('_').padding(1),
+         "a\tb\nxx\tyy\n", "a__b\nxx_yy\n");
+}
+
+#[test]
+fn test_fill_char_multibyte() {
+    iseq(tabw().fill_char('·').padding(1)
+               .column_alignment(0, Alignment::Right),
+         "a\tb\nxxx\tyy\n", "··a·b\nxxx·yy\n");
+}
+
+#[test]
+#[should_panic]
+fn test_fill_char_zero_width() {
+    tabw().fill_char('\t');
+}
+
+#[test]
+fn test_add_column() {
+    let tw = tabw().add_column(1, Column {
+        alignment: Some(Alignment::Center),
+        minwidth: Some(5),
+        padding: Some(1),
+        fill_char: Some('-'),
+        max_width: Some(6),
+    });
+    iseq(tw, "a\tb\tc\nxx\tlong cell\tc\n",
+         "a   --b----c\nxx  lon...-c\n");
+}
+
+#[test]
+fn test_add_column_replaces_settings() {
+    let tw = tabw().column_padding(0, 5).add_column(0, Column::default());
+    iseq(tw, "a\tb\n", "a   b\n");
+}
+
+#[test]
+fn test_column_separator() {
+    iseq(tabw().padding(1).column_separator("|"),
+         "a\tb\tc\nxx\tyy\tzz\n", "a  | b  | c\nxx | yy | zz\n");
+}
+
+#[test]
+fn test_column_separator_contiguous() {
+    iseq(tabw().padding(1).minwidth(0).column_separator("│"),
+         "a\tb\nxx\n", "a │ b\nxx\n");
+}
+
+#[test]
+fn test_single_group() {
+    iseq(tabw().padding(1).minwidth(3).single_group(true),
+"
+fn foobar() {
+ 	let mut x = 1+1;	// addition
+ 	x += 1;	// increment in place
+ 	let y = x * x * x * x;	// multiply!
+
+ 	y += 1;	// this is another group
+ 	y += 2 * 2;	// that is separately aligned
+}
+",
+"
+fn foobar() {
+    let mut x = 1+1;       // addition
+    x += 1;                // increment in place
+    let y = x * x * x * x; // multiply!
+
+    y += 1;                // this is another group
+    y += 2 * 2;            // that is separately aligned
+}
+");
+}
+
+#[test]
+fn test_single_group_no_auto_flush() {
+    let mut tw = tabw().single_group(true);
+    ordie(tw.write_all(b"a\tb\nno tabs\n"));
+    assert!(tw.get_ref().is_empty());
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a   b\nno tabs\n");
+}
+
+#[test]
+fn test_preserve_indent() {
+    iseq(tabw().padding(1).preserve_indent(true),
+"
+fn foobar() {
+    let mut x = 1+1;\t// addition
+
+    x += 1;\t// increment in place
+  \x20
+    let y = x * x * x * x;\t// multiply!
+}
+",
+"
+fn foobar() {
+    let mut x = 1+1;       // addition
+
+    x += 1;                // increment in place
+  \x20
+    let y = x * x * x * x; // multiply!
+}
+");
+}
+
+#[test]
+fn test_preserve_indent_no_auto_flush() {
+    let mut tw = tabw().preserve_indent(true);
+    ordie(tw.write_all(b"a\tb\n  \n"));
+    assert!(tw.get_ref().is_empty());
+    ordie(tw.write_all(b"xx\tyy\nno tabs\n"));
+    assert_eq!(tw.get_ref(), b"a   b\n  \nxx  yy\nno tabs\n");
+}
+
+#[test]
+fn test_blank_line_ends_group() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\n\n"));
+    assert_eq!(tw.get_ref(), b"a   b\n\n");
+    ordie(tw.write_all(b"xxx\ty\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a   b\n\nxxx  y\n");
+}
+
+#[test]
+fn test_ignore_trailing_blank_lines() {
+    let mut tw = tabw().ignore_trailing_blank_lines(true);
+    ordie(tw.write_all(b"a\tb\n\n\n"));
+    assert!(tw.get_ref().is_empty());
+    ordie(tw.write_all(b"xxx\ty\n"));
+    assert_eq!(tw.get_ref(), b"a   b\n\n\n");
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a   b\n\n\nxxx  y\n");
+}
+
+#[test]
+fn test_ignore_trailing_blank_lines_no_tabs() {
+    let mut tw = tabw().ignore_trailing_blank_lines(true);
+    ordie(tw.write_all(b"a\tb\n\nno tabs\n"));
+    assert_eq!(tw.get_ref(), b"a   b\n\nno tabs\n");
+}
+
+#[test]
+fn test_normalize_indent() {
+    iseq(tabw().column_minwidth(0, 6).normalize_indent(true),
+         "    a\tb\n    cc\td\n", "    a       b\n    cc      d\n");
+}
+
+#[test]
+fn test_normalize_indent_disabled() {
+    iseq(tabw().column_minwidth(0, 6),
+         "    a\tb\n    cc\td\n", "    a   b\n    cc  d\n");
+}
+
+#[test]
+fn test_normalize_indent_mixed() {
+    iseq(tabw().padding(1).delimiter(b'|').preserve_indent(true)
+             .normalize_indent(true),
+         "\t  a|b\n\t    ccc|d\n \n\t  ee|f\n",
+         "\t  a     b\n\t    ccc d\n \n\t  ee    f\n");
+}
+
+#[test]
+fn test_strip_trailing_whitespace() {
+    iseq(tabw().strip_trailing_whitespace(true),
+         "a\tb \t\nxx\tyy  \n\t\n", "a   b\nxx  yy\n\n");
+}
+
+#[test]
+fn test_strip_trailing_whitespace_fill_char() {
+    iseq(tabw().strip_trailing_whitespace(true).fill_char('.'),
+         "a\t\t\nxx\t\tyy\n", "a\nxx......yy\n");
+}
+
+#[test]
+fn test_strip_trailing_whitespace_line_prefix() {
+    iseq(tabw().strip_trailing_whitespace(true).line_prefix("  "),
+         "a\tb \n\nxx\tyy\n", "  a   b\n  \n  xx  yy\n");
+}
+
+#[test]
+fn test_trailing_whitespace_kept() {
+    iseq(tabw(), "a\tb \t\nxx\tyy  \n", "a   b   \nxx  yy  \n");
+}
+
+#[test]
+fn test_group_separator_single_cell() {
+    iseq(tabw().group_separator(GroupSeparator::SingleCell),
+         "a\tb\n---\nxxx\ty\n", "a   b\n---\nxxx  y\n");
+}
+
+#[test]
+fn test_group_separator_contains() {
+    let mut tw = tabw().group_separator(GroupSeparator::Contains("---".into()));
+    ordie(tw.write_all(b"no tabs\na\tb\nxxx\ty\n"));
+    assert!(tw.get_ref().is_empty());
+    ordie(tw.write_all(b"--- section\tx\nz\tw\n"));
+    assert_eq!(tw.get_ref(),
+               b"no tabs\na    b\nxxx  y\n--- section  x\n");
+    assert_eq!(tabify(tw, "zzzz\tw\n"),
+               "no tabs\na    b\nxxx  y\n--- section  x\nz     w\nzzzz  w\n");
+}
+
+#[test]
+#[should_panic]
+fn test_group_separator_contains_empty() {
+    tabw().group_separator(GroupSeparator::Contains(String::new()));
+}
+
+#[test]
+fn test_group_separator_line_count() {
+    let mut tw = tabw().group_separator(GroupSeparator::LineCount(2));
+    ordie(tw.write_all(b"a\tb\nxx\tyy\nxxxx\tz\n"));
+    assert_eq!(tw.get_ref(), b"a   b\nxx  yy\n");
+    assert_eq!(tabify(tw, ""), "a   b\nxx  yy\nxxxx  z\n");
+}
+
+#[test]
+fn test_flush_every() {
+    let mut tw = tabw().flush_every(2);
+    ordie(tw.write_all(b"a\tb\nxxx\ty\nc\td\n"));
+    assert_eq!(tw.get_ref(), b"a    b\nxxx  y\n");
+    ordie(tw.write_all(b"no tabs\ne\tf\n"));
+    assert_eq!(tw.get_ref(), b"a    b\nxxx  y\nc   d\nno tabs\n");
+    assert_eq!(ordie(tw.into_string()),
+               "a    b\nxxx  y\nc   d\nno tabs\ne   f\n");
+}
+
+#[test]
+fn test_flush_every_single_group() {
+    let mut tw = tabw().single_group(true).flush_every(3);
+    ordie(tw.write_all(b"a\tb\nno tabs\nxx\tyy\nzzz\t1\n"));
+    assert_eq!(tw.get_ref(), b"a   b\nno tabs\nxx  yy\n");
+    tw.reset();
+}
+
+#[test]
+#[should_panic]
+fn test_flush_every_zero() {
+    tabw().flush_every(0);
+}
+
+#[test]
+fn test_lookahead() {
+    let mut tw = tabw().lookahead(2);
+    ordie(tw.write_all(b"a\tb\nxx\tb\nxxxx\tb\n"));
+    assert_eq!(tw.get_ref(), b"a     b\n");
+    assert_eq!(tw.buffered_bytes(), "xxbxxxxb".len());
+    ordie(tw.write_all(b"x\tb\n"));
+    assert_eq!(tw.get_ref(), b"a     b\nxx    b\n");
+    assert_eq!(ordie(tw.into_string()),
+               "a     b\nxx    b\nxxxx  b\nx     b\n");
+}
+
+#[test]
+fn test_lookahead_wider_line() {
+    let mut tw = tabw().lookahead(1);
+    ordie(tw.write_all(b"a\tb\nxx\tb\nxxxxxx\tb\nno tabs\nc\td\n"));
+    assert_eq!(tw.get_ref(),
+               b"a   b\nxx      b\nxxxxxx  b\nno tabs\n");
+    assert_eq!(ordie(tw.into_string()),
+               "a   b\nxx      b\nxxxxxx  b\nno tabs\nc   d\n");
+}
+
+#[test]
+fn test_write_fmt() {
+    let (a, xx) = ("a", "xx");
+    let mut tw = tabw().lookahead(1);
+    ordie(write!(tw, "{}\t{}\n{}\t{}\n{}{}\t", a, 1, xx, 22, xx, xx));
+    assert_eq!(tw.get_ref(), b"a   1\n");
+    ordie(writeln!(tw, "{}", 333));
+    assert_eq!(ordie(tw.into_string()), "a   1\nxx    22\nxxxx  333\n");
+}
+
+#[test]
+fn test_write_fmt_errors() {
+    let mut tw = TabWriter::new(Trickle {
+        written: vec![],
+        error: Some(::std::io::ErrorKind::BrokenPipe),
+        failed: false,
+    });
+    let err = write!(tw, "a\t{}\n\n", 1).unwrap_err();
+    assert_eq!(err.kind(), ::std::io::ErrorKind::BrokenPipe);
+    tw.reset();
+}
+
+/// A table writer that writes to a `Vec<u8>`, so that one helper can write
+/// a table with any of them.
+trait TableWriter: Write {
+    fn into_vec(self) -> Vec<u8>;
+}
+
+macro_rules! impl_table_writer {
+    ($($name:ident),*) => {
+        $(impl TableWriter for $name<Vec<u8>> {
+            fn into_vec(self) -> Vec<u8> {
+                self.into_inner().unwrap()
+            }
+        })*
+    };
+}
+
+impl_table_writer!(BoxTableWriter, CsvTableWriter, HtmlTableWriter,
+                   LatexTabularWriter, MarkdownTableWriter, RstTableWriter);
+
+fn table<T: TableWriter>(mut w: T, s: &str) -> String {
+    ordie(w.write_all(s.as_bytes()));
+    ordie(String::from_utf8(w.into_vec()))
+}
+
+#[test]
+fn test_tables_empty() {
+    type WriteTable = fn(&str) -> String;
+    let cases: Vec<(&str, WriteTable)> = vec![
+        ("box", |s| table(BoxTableWriter::new(Vec::new()), s)),
+        ("csv", |s| table(CsvTableWriter::new(Vec::new()), s)),
+        ("html", |s| table(HtmlTableWriter::new(Vec::new()), s)),
+        ("latex", |s| table(LatexTabularWriter::new(Vec::new()), s)),
+        ("markdown", |s| table(MarkdownTableWriter::new(Vec::new()), s)),
+        ("rst", |s| table(RstTableWriter::new(Vec::new()), s)),
+    ];
+    for (name, write) in cases {
+        assert_eq!(write(""), "", "{}", name);
+        assert_eq!(write("\n\n"), "", "{}", name);
+    }
+}
+
+#[test]
+fn test_tables_write_header() {
+    type WriteTable = fn(&[&str], &str) -> String;
+    let cases: Vec<(&str, WriteTable, &str)> = vec![
+        ("box", |h, s| {
+            let mut bw = BoxTableWriter::new(Vec::new())
+                .box_style(BoxStyle::Ascii);
+            ordie(bw.write_header(h));
+            table(bw, s)
+        }, "+----+----+\n\
+            | a  | b  |\n\
+            +====+====+\n\
+            | xx | yy |\n\
+            +----+----+\n"),
+        ("markdown", |h, s| {
+            let mut mw = MarkdownTableWriter::new(Vec::new())
+                .header(&["x", "y"]);
+            ordie(mw.write_header(h));
+            table(mw, s)
+        }, "| a   | b   |\n\
+            | --- | --- |\n\
+            | xx  | yy  |\n"),
+        ("rst", |h, s| {
+            let mut rw = RstTableWriter::new(Vec::new());
+            ordie(rw.write_header(h));
+            table(rw, s)
+        }, "+----+----+\n\
+            | a  | b  |\n\
+            +====+====+\n\
+            | xx | yy |\n\
+            +----+----+\n"),
+    ];
+    for (name, write, expected) in cases {
+        assert_eq!(write(&["a", "b"], "xx\tyy\n"), expected, "{}", name);
+    }
+}
+
+#[test]
+fn test_tables_flush_writer() {
+    use std::io::BufWriter;
+
+    type Flushed = fn(&str) -> Vec<u8>;
+    macro_rules! flushed {
+        ($name:ident) => {
+            |s| {
+                let mut w = $name::new(BufWriter::new(Vec::new()));
+                ordie(w.write_all(s.as_bytes()));
+                ordie(w.flush());
+                w.get_ref().get_ref().clone()
+            }
+        };
+    }
+    let cases: Vec<(&str, Flushed)> = vec![
+        ("box", flushed!(BoxTableWriter)),
+        ("csv", flushed!(CsvTableWriter)),
+        ("html", flushed!(HtmlTableWriter)),
+        ("latex", flushed!(LatexTabularWriter)),
+        ("markdown", flushed!(MarkdownTableWriter)),
+        ("rst", flushed!(RstTableWriter)),
+    ];
+    for (name, flushed) in cases {
+        assert!(!flushed("a\tb\n").is_empty(), "{}", name);
+    }
+}
+
+#[test]
+fn test_markdown_table() {
+    assert_eq!(table(MarkdownTableWriter::new(Vec::new()),
+                        "name\tvalue\nfoo\t1\n\nfoobar\t|\n"),
+               "| name   | value |\n\
+                | ------ | ----- |\n\
+                | foo    | 1     |\n\
+                | foobar | \\|    |\n");
+}
+
+#[test]
+fn test_markdown_table_alignment_and_ragged() {
+    let mw = MarkdownTableWriter::new(Vec::new())
+        .header(&["a", "b", "c"])
+        .column_alignment(0, Alignment::Right)
+        .column_alignment(1, Alignment::Center);
+    assert_eq!(table(mw, "x\ty\nxxxxx\n"),
+               "|     a |  b  | c   |\n\
+                | ----: | :-: | --- |\n\
+                |     x |  y  |     |\n\
+                | xxxxx |     |     |\n");
+}
+
+#[test]
+fn test_html_table() {
+    let hw = HtmlTableWriter::new(Vec::new()).column_class(0, "a\"b");
+    assert_eq!(table(hw, "<x>\t\"y\"\n\nz\n"), "<table>\n\
+                         <tr><td class=\"a&quot;b\">&lt;x&gt;</td>\
+                         <td>&quot;y&quot;</td></tr>\n\
+                         <tr><td class=\"a&quot;b\">z</td></tr>\n\
+                         </table>\n");
+}
+
+#[test]
+fn test_html_table_empty_cells() {
+    assert_eq!(table(HtmlTableWriter::new(Vec::new()), "a\tb\n\t\n"),
+               "<table>\n\
+                <tr><td>a</td><td>b</td></tr>\n\
+                <tr><td></td><td></td></tr>\n\
+                </table>\n");
+}
+
+#[test]
+fn test_html_table_write_header() {
+    let mut hw = HtmlTableWriter::new(Vec::new());
+    ordie(hw.write_header(&["a", "b"]));
+    ordie(hw.write_all(b"1\t2\n"));
+    ordie(hw.flush());
+    assert_eq!(table(hw, "3\n"), "<table>\n\
+                         <tr><th>a</th><th>b</th></tr>\n\
+                         <tr><td>1</td><td>2</td></tr>\n\
+                         </table>\n\
+                         <table>\n\
+                         <tr><td>3</td></tr>\n\
+                         </table>\n");
+}
+
+#[test]
+fn test_latex_tabular() {
+    let lw = LatexTabularWriter::new(Vec::new())
+        .column_alignment(1, Alignment::Center);
+    assert_eq!(table(lw, "a\tb\tc\n\nxxx\t12\n"),
+               "\\begin{tabular}{lcl}\n\
+                a   & b  & c \\\\\n\
+                xxx & 12 &   \\\\\n\
+                \\end{tabular}\n");
+}
+
+#[test]
+fn test_latex_tabular_escapes() {
+    let lw = LatexTabularWriter::new(Vec::new());
+    assert_eq!(table(lw, "50% of $x_1\t{#}\\^~\n"),
+               "\\begin{tabular}{ll}\n\
+                50\\% of \\$x\\_1 & \
+                \\{\\#\\}\\textbackslash{}\\textasciicircum{}\
+                \\textasciitilde{} \\\\\n\
+                \\end{tabular}\n");
+}
+
+#[test]
+fn test_rst_table() {
+    assert_eq!(table(RstTableWriter::new(Vec::new()), "a\tb\tc\n\nxxx\tÞ\n"),
+               "+-----+---+---+\n\
+                | a   | b | c |\n\
+                +-----+---+---+\n\
+                | xxx | Þ |   |\n\
+                +-----+---+---+\n");
+}
+
+#[test]
+fn test_rst_table_header() {
+    let rw = RstTableWriter::new(Vec::new()).header_row(true);
+    assert_eq!(table(rw, "a\tb\nxx\tyy\nz\n"),
+               "+----+----+\n\
+                | a  | b  |\n\
+                +====+====+\n\
+                | xx | yy |\n\
+                +----+----+\n\
+                | z  |    |\n\
+                +----+----+\n");
+}
+
+#[test]
+fn test_csv_table() {
+    assert_eq!(table(CsvTableWriter::new(Vec::new()),
+                   "a\tb\tc\r\n\nx,y\t\"q\"\n\tz\n"),
+               "a,b,c\r\n\"x,y\",\"\"\"q\"\"\",\r\n,z,\r\n");
+}
+
+#[test]
+fn test_csv_table_empty_cells() {
+    assert_eq!(table(CsvTableWriter::new(Vec::new()), "a\tb\n\t\nc\td\n"),
+               "a,b\r\n,\r\nc,d\r\n");
+}
+
+#[test]
+fn test_csv_table_quotes_line_breaks() {
+    use writer::quote;
+
+    assert_eq!(table(CsvTableWriter::new(Vec::new()), "a\rb\tc\n"),
+               "\"a\rb\",c\r\n");
+    assert_eq!(quote("a\nb"), "\"a\nb\"");
+    assert_eq!(quote("a\r\nb"), "\"a\r\nb\"");
+}
+
+#[test]
+fn test_csv_table_line_ending() {
+    let cw = CsvTableWriter::new(Vec::new()).line_ending(LineEnding::Lf);
+    assert_eq!(table(cw, "a\tb\nc\td\n"), "a,b\nc,d\n");
+}
+
+#[test]
+fn test_csv_table_utf8_bom() {
+    let mut cw = CsvTableWriter::new(Vec::new()).utf8_bom(true);
+    ordie(cw.flush());
+    ordie(cw.write_all(b"a\tb\n"));
+    ordie(cw.flush());
+    ordie(cw.write_all(b"c\n"));
+    assert_eq!(table(cw, ""), "\u{feff}a,b\r\nc\r\n");
+}
+
+#[test]
+fn test_box_table() {
+    let bw = BoxTableWriter::new(Vec::new())
+        .column_alignment(1, Alignment::Right);
+    assert_eq!(table(bw, "a\tb\tc\n\nxxx\t12\n"),
+               "┌─────┬────┬───┐\n\
+                │ a   │  b │ c │\n\
+                │ xxx │ 12 │   │\n\
+                └─────┴────┴───┘\n");
+}
+
+#[test]
+fn test_box_table_no_borders() {
+    let bw = BoxTableWriter::new(Vec::new())
+        .box_style(BoxStyle::None)
+        .header_separator(true)
+        .column_alignment(1, Alignment::Right);
+    assert_eq!(table(bw, "name\tn\nfoo\t12\nx\n"),
+               "name   n\n\
+                foo   12\n\
+                x\n");
+}
+
+#[test]
+fn test_box_table_no_borders_trailing_spaces() {
+    let bw = BoxTableWriter::new(Vec::new()).box_style(BoxStyle::None);
+    assert_eq!(table(bw, "a\tb \nxx\ty\n"), "a   b \nxx  y\n");
+}
+
+#[test]
+fn test_box_table_ascii_header() {
+    let bw = BoxTableWriter::new(Vec::new())
+        .box_style(BoxStyle::Ascii)
+        .header_separator(true);
+    assert_eq!(table(bw, "name\tn\nfoo\t1\n"),
+               "+------+---+\n\
+                | name | n |\n\
+                +======+===+\n\
+                | foo  | 1 |\n\
+                +------+---+\n");
+}
+
+#[test]
+fn test_write_row() {
+    let mut tw = tabw();
+    ordie(tw.write_row(&["a", "b", "c"]));
+    ordie(tw.write_row(&["xx".to_string(), "yy".to_string()]));
+    ordie(tw.write_row_no_newline(&["x", "y"]));
+    ordie(tw.write_all(b"z\n"));
+    assert_eq!(tabify(tw, ""), "a   b   c\nxx  yy\nx   yz\n");
+}
+
+#[test]
+fn test_write_comment_aligned() {
+    let mut tw = tabw().padding(1);
+    ordie(tw.write_comment_aligned("let mut x = 1;", "// one"));
+    ordie(tw.write_comment_aligned("x += 1;", "// two"));
+    ordie(tw.write_all(b"}\n"));
+    ordie(tw.write_comment_aligned("f(x);", "// call"));
+    assert_eq!(tabify(tw, ""),
+               "let mut x = 1; // one\nx += 1;        // two\n\
+                }\nf(x); // call\n");
+}
+
+#[test]
+fn test_write_header() {
+    let mut tw = tabw();
+    ordie(tw.write_header(&["a", "b"]));
+    ordie(tw.write_row(&["xx", "yy"]));
+    let err = tw.write_header(&["c", "d"]).unwrap_err();
+    assert_eq!(err.kind(), ::std::io::ErrorKind::InvalidInput);
+    assert_eq!(tabify(tw, ""), "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_comment_mode() {
+    iseq(tabw().comment_mode("//"),
+         "// header\nx = 1; // one\nlonger = 2;   // two\nno comment\n",
+         "// header\nx = 1;       // one\nlonger = 2;  // two\nno comment\n");
+}
+
+#[test]
+fn test_comment_mode_first_prefix() {
+    iseq(tabw().comment_mode("#"), "a # b # c\nab # d\n",
+         "a   # b # c\nab  # d\n");
+}
+
+#[test]
+fn test_comment_mode_split_writes() {
+    let mut tw = tabw().comment_mode("--");
+    ordie(tw.write_all(b"select 1; -"));
+    ordie(tw.write_all(b"- one\nselect 22; "));
+    ordie(tw.write_all(b"-- two\n"));
+    assert_eq!(tabify(tw, ""), "select 1;   -- one\nselect 22;  -- two\n");
+}
+
+#[test]
+#[should_panic(expected = "comment prefix must not be empty")]
+fn test_comment_mode_empty() {
+    tabw().comment_mode("");
+}
+
+#[test]
+fn test_write_row_auto_flush() {
+    let mut tw = tabw();
+    ordie(tw.write_row(&["a", "b"]));
+    ordie(tw.write_row(&["no tabs"]));
+    assert_eq!(tw.get_ref(), b"a   b\nno tabs\n");
+}
+
+#[test]
+fn test_write_row_custom_delimiter() {
+    let mut tw = tabw().delimiter(b'|');
+    ordie(tw.write_row(&["a", "b"]));
+    assert_eq!(tabify(tw, "xx|yy\n"), "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_extend_rows() {
+    let rows = vec![
+        vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        vec![],
+        vec!["xx".to_string(), "yy".to_string()],
+        vec!["single".to_string()],
+    ];
+    let mut tw = tabw();
+    ordie(tw.extend_rows(rows));
+    assert_eq!(tabify(tw, ""), "a   b   c\n\nxx  yy\nsingle\n");
+}
+
+#[test]
+fn test_extend_rows_adapters() {
+    let mut tw = tabw();
+    ordie(tw.extend_rows((1..4).map(|i| vec![i.to_string(); i])));
+    assert_eq!(tabify(tw, ""), "1\n2   2\n3   3   3\n");
+}
+
+#[test]
+fn test_from_writer() {
+    let mut tw: TabWriter<Vec<u8>> = vec![].into();
+    ordie(tw.write_all(b"a\tb\nxx\tyy\n"));
+    assert_eq!(ordie(tw.into_string()), "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_write_by_mut_ref() {
+    fn write_table<W: Write>(mut w: W) -> ::std::io::Result<()> {
+        w.write_all(b"a\tb\nxx\tyy\n")
+    }
+
+    let mut tw = tabw();
+    ordie(write_table(&mut tw));
+    ordie(write_table(&mut tw));
+    assert_eq!(ordie(tw.into_string()), "a   b\nxx  yy\na   b\nxx  yy\n");
+}
+
+#[test]
+fn test_extend_str() {
+    let mut tw = tabw();
+    tw.extend(vec!["a\tb\n", "xx\t", "yy\n"]);
+    tw.extend(vec!["no tabs\n".to_string()]);
+    assert_eq!(tw.get_ref(), b"a   b\nxx  yy\nno tabs\n");
+}
+
+#[test]
+fn test_from_iterator() {
+    let rows = vec!["a\tb\n", "xx\tyy\n"];
+    let tw: TabWriter<Vec<u8>> = rows.into_iter().collect();
+    assert_eq!(ordie(tw.into_string()), "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_lines_and_columns_count() {
+    let mut tw = tabw();
+    assert_eq!((tw.lines_count(), tw.columns_count()), (0, 0));
+    ordie(tw.write_all(b"a\tb\tc\nxx"));
+    assert_eq!((tw.lines_count(), tw.columns_count()), (2, 3));
+    ordie(tw.write_all(b"\tyy\t\tzz"));
+    assert_eq!((tw.lines_count(), tw.columns_count()), (2, 4));
+    ordie(tw.flush());
+    assert_eq!((tw.lines_count(), tw.columns_count()), (0, 0));
+    ordie(tw.write_all(b"a\tb\n"));
+    assert_eq!((tw.lines_count(), tw.columns_count()), (1, 2));
+    tw.reset();
+    assert_eq!((tw.lines_count(), tw.columns_count()), (0, 0));
+}
+
+#[test]
+fn test_buffered_bytes() {
+    let mut tw = tabw();
+    assert_eq!(tw.buffered_bytes(), 0);
+    ordie(tw.write_all(b"ab\tc\n"));
+    assert_eq!(tw.buffered_bytes(), 3);
+    ordie(tw.write_all("é\tx".as_bytes()));
+    assert_eq!(tw.buffered_bytes(), 6);
+    ordie(tw.flush());
+    assert_eq!(tw.buffered_bytes(), 0);
+}
+
+#[test]
+fn test_custom_renderer() {
+    // Parse "a|bb|c\nxxx|y\n" into cells that refer to a single buffer.
+    let mut buf = vec![];
+    let mut lines = vec![];
+    for line in "a|bb|c\nxxx|y".split('\n') {
+        let mut cells = vec![];
+        for cell in line.split('|') {
+            let start = buf.len();
+            buf.extend_from_slice(cell.as_bytes());
+            cells.push(Cell::from_buf(&buf, start, cell.len()));
+        }
+        lines.push(cells);
+    }
+
+    let mut out = String::new();
+    for (line, widths) in lines.iter().zip(cell_widths(&lines, 0)) {
+        for (i, cell) in line.iter().enumerate() {
+            let text = ordie(::std::str::from_utf8(
+                &buf[cell.start..cell.start + cell.size]));
+            match widths.get(i) {
+                Some(&w) => out.push_str(&format!("{:<w$} | ", text, w = w)),
+                None => out.push_str(text),
+            }
+        }
+        out.push('\n');
+    }
+    assert_eq!(out, "a   | bb | c\nxxx | y\n");
+}
+
+#[test]
+fn test_flush_with_stats() {
+    let mut tw = tabw();
+    assert_eq!(ordie(tw.flush_with_stats()), Default::default());
+    ordie(tw.write_all(b"a\tb\n\nxx\tyy\tzz"));
+    let stats = ordie(tw.flush_with_stats());
+    assert_eq!((stats.lines, stats.groups, stats.max_columns), (3, 2, 3));
+    assert_eq!(stats.bytes, tw.get_ref().len());
+    ordie(tw.write_all(b"x\n"));
+    let stats = ordie(tw.flush_with_stats());
+    assert_eq!((stats.lines, stats.groups, stats.bytes), (1, 1, 2));
+}
+
+#[test]
+fn test_display() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\nno tabs\nxx\ty"));
+    assert_eq!(tw.get_ref(), b"a   b\nno tabs\n");
+    assert_eq!(format!("{}", tw), "a   b\nno tabs\nxx  y");
+    ordie(tw.write_all(b"y\nxxxx\tz\n"));
+    assert_eq!(ordie(tw.into_string()), "a   b\nno tabs\nxx    yy\nxxxx  z\n");
+}
+
+#[test]
+fn test_into_iter() {
+    let mut tw = tabw().line_ending(LineEnding::CrLf);
+    ordie(tw.write_all(b"a\tb\n\nxx\tyy\n"));
+    let rows: Vec<String> = tw.into_iter().collect();
+    assert_eq!(rows, vec!["a   b", "", "xx  yy"]);
+
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb"));
+    assert_eq!(tw.into_iter().collect::<Vec<_>>(), vec!["a   b"]);
+    assert_eq!(tabw().into_iter().next(), None);
+}
+
+#[test]
+fn test_clone_checkpoint() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\n"));
+    let checkpoint = tw.clone();
+    ordie(tw.write_all(b"a very long cell\tc\n"));
+    tw.reset();
+    let mut tw = checkpoint;
+    ordie(tw.write_all(b"xx\tyy\n"));
+    assert_eq!(ordie(tw.into_string()), "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_clone_dropped_unused() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\n"));
+    drop(tw.clone());
+    assert_eq!(ordie(tw.into_string()), "a   b\n");
+}
+
+#[test]
+fn test_debug() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\nc\td"));
+    assert_eq!(format!("{:?}", tw),
+               "TabWriter { w: [], buf: \"abcd\", \
+                lines: [[Cell { start: 0, width: 1, size: 1 }, \
+                Cell { start: 1, width: 1, size: 1 }], \
+                [Cell { start: 2, width: 1, size: 1 }]], \
+                curcell: Cell { start: 3, width: 0, size: 1 }, \
+                minwidth: 2, padding: 2 }");
+    tw.reset();
+}
+
+#[test]
+fn test_debug_invalid_utf8() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\xff"));
+    assert!(format!("{:?}", tw).contains("buf: 61 ff,"));
+    tw.reset();
+}
+
+#[test]
+fn test_with_capacity() {
+    let mut tw = TabWriter::with_capacity(Vec::new(), 4096);
+    assert!(tw.buf_capacity() >= 4096);
+    ordie(tw.write_all(b"a\tb\nxx\tyy\n"));
+    ordie(tw.flush());
+    assert!(tw.buf_capacity() >= 4096);
+    assert_eq!(ordie(tw.into_string()), "a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_flush_and_widths() {
+    let mut tw = tabw().padding(1).minwidth(0);
+    // The first group is written before the flush, so it is not included.
+    ordie(tw.write_all(b"a\tb\nno tabs\nxx\tyyy\tz\nw\tv\n"));
+    assert_eq!(ordie(tw.flush_and_widths()), vec![2, 3]);
+    assert_eq!(ordie(tw.flush_and_widths()), Vec::<usize>::new());
+    assert_eq!(tw.get_ref(), b"a b\nno tabs\nxx yyy z\nw  v\n");
+}
+
+#[test]
+fn test_column_widths_last_flush() {
+    let mut tw = tabw().padding(1).minwidth(0);
+    assert!(tw.column_widths_last_flush().is_empty());
+    // The group ends automatically with the line without tabs.
+    ordie(tw.write_all(b"aaa\tb\nno tabs\nx\tyy"));
+    assert_eq!(tw.column_widths_last_flush(), &[3]);
+    ordie(tw.write_all(b"\tz\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.column_widths_last_flush(), &[1, 2]);
+    tw.reset();
+    assert_eq!(tw.column_widths_last_flush(), &[1, 2]);
+}
+
+#[test]
+fn test_as_ref_as_mut() {
+    fn written<W: AsRef<Vec<u8>>>(w: &W) -> usize {
+        w.as_ref().len()
+    }
+
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\nno tabs\n"));
+    assert_eq!(written(&tw), b"a   b\nno tabs\n".len());
+    tw.as_mut().clear();
+    assert_eq!(written(&tw), 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "TabWriter dropped with unflushed data")]
+fn test_drop_unflushed() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\n"));
+}
+
+#[test]
+fn test_drop_flushed_or_reset() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\n"));
+    ordie(tw.flush());
+    ordie(tw.write_all(b"c\td\n"));
+    tw.reset();
+}
+
+#[test]
+fn test_drop_after_failed_flush() {
+    let mut tw = TabWriter::new(Trickle {
+        written: vec![],
+        error: Some(::std::io::ErrorKind::Other),
+        failed: false,
+    });
+    ordie(tw.write_all(b"a\tb\n"));
+    assert!(tw.flush().is_err());
+}
+
+#[test]
+fn test_take_inner() {
+    let mut tw = tabw().padding(1);
+    ordie(tw.write_all(b"a\tb\nxxx\ty\n"));
+    assert_eq!(ordie(tw.take_inner()), b"a   b\nxxx y\n");
+    assert_eq!(ordie(tw.take_inner()), b"");
+    ordie(tw.write_all(b"c\td"));
+    assert_eq!(ordie(tw.take_inner()), b"c  d");
+    assert!(tw.get_ref().is_empty());
+}
+
+#[test]
+fn test_aligner() {
+    let mut ta = TabAligner::new().padding(1);
+    ordie(ta.write_all(b"a\tbbb\tc\nxx\ty\n\nla"));
+    assert_eq!(ta.rows().count(), 0);
+    ordie(ta.write_all("st\tö\t".as_bytes()));
+    ordie(ta.flush());
+
+    let rows: Vec<Vec<(&str, usize, usize, usize)>> = ta.rows()
+        .map(|row| row.map(|cell| {
+            (cell.content, cell.width, cell.padded_width, cell.padding)
+        }).collect())
+        .collect();
+    assert_eq!(rows, vec![
+        vec![("a", 1, 3, 2), ("bbb", 3, 4, 1), ("c", 1, 1, 0)],
+        vec![("xx", 2, 3, 1), ("y", 1, 1, 0)],
+        vec![("", 0, 0, 0)],
+        vec![("last", 4, 5, 1), ("ö", 1, 1, 0)],
+    ]);
+
+    // The text of every row is the same as `TabWriter` writes.
+    let rendered: Vec<String> = ta.rows().map(|row| {
+        row.map(|cell| format!("{}{}", cell.content, " ".repeat(cell.padding)))
+           .collect()
+    }).collect();
+    let input = "a\tbbb\tc\nxx\ty\n\nlast\tö\t";
+    assert_eq!(rendered.join("\n"), tabify(tabw().padding(1), input));
+
+    ta.clear();
+    assert_eq!(ta.rows().count(), 0);
+    ordie(ta.write_all(b"z\n"));
+    ordie(ta.flush());
+    assert_eq!(ta.rows().count(), 1);
+}
+
+#[test]
+fn test_new_buffered() {
+    let mut tw = TabWriter::new_buffered(vec![]);
+    ordie(tw.write_all(b"a\tb\n\nxx\tyy\n"));
+    // The first group was written, but only to the `BufWriter`.
+    assert!(tw.get_ref().get_ref().is_empty());
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref().get_ref(), b"a   b\n\nxx  yy\n");
+
+    let tw = TabWriter::new_buffered_with_capacity(vec![], 16);
+    assert_eq!(tw.get_ref().capacity(), 16);
+}
+
+#[test]
+fn test_utf8_bom() {
+    let mut tw = tabw().utf8_bom(true);
+    ordie(tw.flush());
+    assert!(tw.get_ref().is_empty());
+    ordie(tw.write_all(b"a\tb\nno tabs\n"));
+    ordie(tw.write_all(b"xx\tyy\n"));
+    ordie(tw.flush());
+    ordie(tw.write_all(b"z\n"));
+    assert_eq!(ordie(tw.into_string()),
+               "\u{feff}a   b\nno tabs\nxx  yy\nz\n");
+}
+
+#[test]
+fn test_utf8_bom_lookahead() {
+    let mut tw = tabw().utf8_bom(true).lookahead(1);
+    ordie(tw.write_all(b"a\tb\nxx\tyy\nc\td\n"));
+    assert_eq!(tw.get_ref(), b"\xEF\xBB\xBFa   b\nxx  yy\n");
+    assert_eq!(ordie(tw.flush_with_stats()).bytes, tw.get_ref().len());
+}
+
+#[test]
+fn test_seek_flushes() {
+    use std::io::{Cursor, Seek, SeekFrom};
+
+    let mut tw = TabWriter::new(Cursor::new(vec![]));
+    ordie(tw.write_all(b"a\tb\nxx\tyy"));
+    assert!(tw.get_ref().get_ref().is_empty());
+    assert_eq!(ordie(tw.seek(SeekFrom::Start(0))), 0);
+    assert_eq!(tw.get_ref().get_ref(), b"a   b\nxx  yy");
+    ordie(tw.write_all(b"zz\tq\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref().get_ref(), b"zz  q\nxx  yy");
+}
+
+#[test]
+fn test_boxed_writers() {
+    use std::io::{BufWriter, Cursor};
+
+    let input = "a\tb\nxxx\tyy\n";
+    let mut outputs = vec![vec![]; 3];
+    {
+        let mut it = outputs.iter_mut();
+        let writers: Vec<Box<dyn Write>> = vec![
+            Box::new(it.next().unwrap()),
+            Box::new(Cursor::new(it.next().unwrap())),
+            Box::new(BufWriter::new(it.next().unwrap())),
+        ];
+        for w in writers {
+            let mut tw = TabWriter::new(w);
+            ordie(tw.write_all(input.as_bytes()));
+            ordie(tw.flush());
+        }
+    }
+    for output in &outputs {
+        assert_eq!(ordie(::std::str::from_utf8(output)), "a    b\nxxx  yy\n");
+    }
+}
+
+/// A reader that returns one byte at a time, and an error once it has
+/// returned all of them if `fail` is set.
+struct Dribble {
+    text: &'static [u8],
+    fail: bool,
+}
+
+impl ::std::io::Read for Dribble {
+    fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize> {
+        if self.text.is_empty() && self.fail {
+            return Err(::std::io::ErrorKind::Other.into());
+        }
+        let n = ::std::cmp::min(1, self.text.len());
+        buf[..n].copy_from_slice(&self.text[..n]);
+        self.text = &self.text[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_tab_reader() {
+    use std::io::Read;
+
+    let text = b"a\tb\nxx\tyy\nno tabs\nabc\td";
+    let mut tr = TabReader::new(Dribble { text, fail: false });
+    let mut aligned = String::new();
+    ordie(tr.read_to_string(&mut aligned));
+    assert_eq!(aligned, "a   b\nxx  yy\nno tabs\nabc  d");
+    assert!(tr.into_inner().text.is_empty());
+}
+
+#[test]
+fn test_tab_reader_reads_complete_groups() {
+    use std::io::Read;
+
+    let text = b"a\tb\nxx\tyy\nno tabs\nabc\td\n";
+    let mut tr = TabReader::new(Dribble { text, fail: true });
+    let mut aligned = vec![0; 15];
+    ordie(tr.read_exact(&mut aligned));
+    assert_eq!(aligned, b"a   b\nxx  yy\nno");
+    let mut rest = vec![];
+    assert!(tr.read_to_end(&mut rest).is_err());
+    assert_eq!(rest, b" tabs\n");
+}
+
+#[test]
+fn test_default() {
+    let mut tw: TabWriter<Vec<u8>> = Default::default();
+    ordie(tw.write_all(b"a\tb\nxx\tyy\n"));
+    assert_eq!(ordie(tw.into_string()), "a   b\nxx  yy\n");
+}
+
+/// A writer that accepts at most three bytes per call. If `error` is set,
+/// then every other call fails with an error of that kind instead.
+#[derive(Default)]
+struct Trickle {
+    written: Vec<u8>,
+    error: Option<::std::io::ErrorKind>,
+    failed: bool,
+}
+
+impl Write for Trickle {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        if let Some(kind) = self.error {
+            self.failed = !self.failed;
+            if self.failed {
+                return Err(kind.into());
+            }
+        }
+        let n = ::std::cmp::min(3, buf.len());
+        self.written.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_partial_writes() {
+    let mut tw = TabWriter::new(Trickle::default());
+    ordie(tw.write_all(b"a\tb\nxx\tyy\nno tabs\nabc\td\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref().written, b"a   b\nxx  yy\nno tabs\nabc  d\n");
+}
+
+#[test]
+fn test_interrupted_writes() {
+    let mut tw = TabWriter::new(Trickle {
+        error: Some(::std::io::ErrorKind::Interrupted),
+        ..Trickle::default()
+    });
+    ordie(tw.write_all(b"a\tb\nxx\tyy\n"));
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref().written, b"a   b\nxx  yy\n");
+}
+
+#[test]
+fn test_would_block_is_returned() {
+    let mut tw = TabWriter::new(Trickle {
+        error: Some(::std::io::ErrorKind::WouldBlock),
+        ..Trickle::default()
+    });
+    ordie(tw.write_all(b"a\tb\nxx\tyy\n"));
+    let err = tw.flush().unwrap_err();
+    assert_eq!(err.kind(), ::std::io::ErrorKind::WouldBlock);
+}
+
+/// An asynchronous writer that is only ready on every other poll, and then
+/// accepts at most three bytes.
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[derive(Default)]
+struct Stubborn {
+    written: Vec<u8>,
+    ready: bool,
+}
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+impl Stubborn {
+    fn poll_write_some(
+        &mut self,
+        cx: &mut ::std::task::Context,
+        buf: &[u8],
+    ) -> ::std::task::Poll<::std::io::Result<usize>> {
+        use std::task::Poll;
+
+        self.ready = !self.ready;
+        if !self.ready {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let n = ::std::cmp::min(3, buf.len());
+        self.written.extend_from_slice(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl ::tokio::io::AsyncWrite for Stubborn {
+    fn poll_write(
+        mut self: ::std::pin::Pin<&mut Self>,
+        cx: &mut ::std::task::Context,
+        buf: &[u8],
+    ) -> ::std::task::Poll<::std::io::Result<usize>> {
+        self.poll_write_some(cx, buf)
+    }
+
+    fn poll_flush(
+        self: ::std::pin::Pin<&mut Self>,
+        _: &mut ::std::task::Context,
+    ) -> ::std::task::Poll<::std::io::Result<()>> {
+        ::std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: ::std::pin::Pin<&mut Self>,
+        _: &mut ::std::task::Context,
+    ) -> ::std::task::Poll<::std::io::Result<()>> {
+        ::std::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl ::futures_io::AsyncWrite for Stubborn {
+    fn poll_write(
+        mut self: ::std::pin::Pin<&mut Self>,
+        cx: &mut ::std::task::Context,
+        buf: &[u8],
+    ) -> ::std::task::Poll<::std::io::Result<usize>> {
+        self.poll_write_some(cx, buf)
+    }
+
+    fn poll_flush(
+        self: ::std::pin::Pin<&mut Self>,
+        _: &mut ::std::task::Context,
+    ) -> ::std::task::Poll<::std::io::Result<()>> {
+        ::std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: ::std::pin::Pin<&mut Self>,
+        _: &mut ::std::task::Context,
+    ) -> ::std::task::Poll<::std::io::Result<()>> {
+        ::std::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+const ASYNC_INPUT: &str = "a\tb\nxx\tyy\nno tabs\nabc\td\n";
+
+/// Returns a waker that does nothing, since the tests poll in a loop.
+/// (`Waker::noop` is too new for our minimum Rust version.)
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+fn noop_waker() -> ::std::task::Waker {
+    use std::task::{RawWaker, RawWakerVTable, Waker};
+
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(::std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable =
+        RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(clone(::std::ptr::null())) }
+}
+
+#[test]
+#[cfg(feature = "tokio")]
+fn test_tokio_async_write() {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::AsyncWrite;
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut tw = TabWriter::new(Stubborn::default());
+    for chunk in ASYNC_INPUT.as_bytes().chunks(4) {
+        loop {
+            match Pin::new(&mut tw).poll_write(&mut cx, chunk) {
+                Poll::Ready(n) => {
+                    assert_eq!(ordie(n), chunk.len());
+                    break;
+                }
+                Poll::Pending => {}
+            }
+        }
+    }
+    while Pin::new(&mut tw).poll_shutdown(&mut cx).is_pending() {}
+    assert_eq!(ordie(String::from_utf8(tw.get_ref().written.clone())),
+               tabify(tabw(), ASYNC_INPUT));
+}
+
+#[test]
+#[cfg(all(feature = "tokio", debug_assertions))]
+#[should_panic(expected = "TabWriter dropped with unflushed data")]
+fn test_tokio_drop_pending() {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::AsyncWrite;
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut tw = TabWriter::new(Stubborn::default());
+    match Pin::new(&mut tw).poll_write(&mut cx, b"a\tb\n") {
+        Poll::Ready(n) => assert_eq!(ordie(n), 4),
+        Poll::Pending => panic!("nothing was pending"),
+    }
+    assert!(Pin::new(&mut tw).poll_flush(&mut cx).is_pending());
+}
+
+#[test]
+#[cfg(feature = "tokio")]
+fn test_tokio_unflushed_until_written() {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::AsyncWrite;
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut tw = TabWriter::new(Stubborn::default());
+    match Pin::new(&mut tw).poll_write(&mut cx, b"a\tb\n") {
+        Poll::Ready(n) => assert_eq!(ordie(n), 4),
+        Poll::Pending => panic!("nothing was pending"),
+    }
+    while Pin::new(&mut tw).poll_flush(&mut cx).is_pending() {}
+    assert_eq!(tw.get_ref().written, b"a   b\n");
+}
+
+#[test]
+#[cfg(feature = "futures-io")]
+fn test_futures_async_write() {
+    use futures_io::AsyncWrite;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut tw = TabWriter::new(Stubborn::default());
+    for chunk in ASYNC_INPUT.as_bytes().chunks(4) {
+        loop {
+            match Pin::new(&mut tw).poll_write(&mut cx, chunk) {
+                Poll::Ready(n) => {
+                    assert_eq!(ordie(n), chunk.len());
+                    break;
+                }
+                Poll::Pending => {}
+            }
+        }
+    }
+    while Pin::new(&mut tw).poll_close(&mut cx).is_pending() {}
+    assert_eq!(ordie(String::from_utf8(tw.get_ref().written.clone())),
+               tabify(tabw(), ASYNC_INPUT));
+}
+
+#[test]
+fn test_complete_groups_written_immediately() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"a\tb\nxx\tyy\n\nabc\td\n"));
+    assert_eq!(tw.get_ref(), b"a   b\nxx  yy\n\n");
+    ordie(tw.flush());
+    assert_eq!(tw.get_ref(), b"a   b\nxx  yy\n\nabc  d\n");
+}
+
+#[test]
+fn test_reset_partial_cell() {
+    let mut tw = tabw();
+    ordie(tw.write_all(b"garbage\tpart"));
+    tw.reset();
+    ordie(tw.write_all(b"ial\txx\nyy\tzz"));
+    assert_eq!(tabify(tw, ""), "ial  xx\nyy   zz");
+}
+
+/// Tests for text with ANSI color codes. With the `ansi_formatting` feature,
+/// escape sequences take up no columns, so colored text is aligned by what
+/// is visible.
+mod ansi {
+    use super::{iseq, tabw};
+
+    #[test]
+    #[cfg(feature = "ansi_formatting")]
+    fn test_first_column() {
+        iseq(tabw(), "\x1b[31ma\x1b[0m\tb\nxx\tyy\n",
+             "\x1b[31ma\x1b[0m   b\nxx  yy\n");
+    }
+
+    #[test]
+    #[cfg(feature = "ansi_formatting")]
+    fn test_color_spanning_tab() {
+        iseq(tabw(), "\x1b[31ma\tb\x1b[0m\tc\nxxx\tyyy\tz\n",
+             "\x1b[31ma    b\x1b[0m    c\nxxx  yyy  z\n");
+    }
+
+    #[test]
+    #[cfg(feature = "ansi_formatting")]
+    fn test_reset_at_end_of_cell() {
+        iseq(tabw(), "a\x1b[0m\tb\nxxx\ty\n", "a\x1b[0m    b\nxxx  y\n");
+    }
+
+    #[test]
+    #[cfg(feature = "ansi_formatting")]
+    fn test_nested() {
+        iseq(tabw(), "\x1b[1m\x1b[31mab\x1b[0m\x1b[0m\tc\nxxx\ty\n",
+             "\x1b[1m\x1b[31mab\x1b[0m\x1b[0m   c\nxxx  y\n");
+    }
+
+    #[test]
+    #[cfg(feature = "ansi_formatting")]
+    fn test_256_color() {
+        iseq(tabw(), "\x1b[38;5;196ma\x1b[0m\tb\nxxx\ty\n",
+             "\x1b[38;5;196ma\x1b[0m    b\nxxx  y\n");
+    }
+
+    #[test]
+    #[cfg(feature = "ansi_formatting")]
+    fn test_rgb_color() {
+        iseq(tabw(), "\x1b[48;2;255;0;128ma\x1b[0m\tb\nxxx\ty\n",
+             "\x1b[48;2;255;0;128ma\x1b[0m    b\nxxx  y\n");
+    }
+
+    #[test]
+    #[cfg(feature = "ansi_formatting")]
+    fn test_codes_around_tab() {
+        iseq(tabw(), "a\x1b[31m\t\x1b[0mb\nxxx\ty\n",
+             "a\x1b[31m    \x1b[0mb\nxxx  y\n");
+    }
+
+    /// Without `ansi_formatting`, the printable characters of an escape
+    /// sequence are counted like any other text.
+    #[test]
+    #[cfg(not(feature = "ansi_formatting"))]
+    fn test_counted_without_feature() {
+        iseq(tabw(), "\x1b[31ma\x1b[0m\tb\nxx\tyy\n",
+             "\x1b[31ma\x1b[0m  b\nxx        yy\n");
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_table {
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+    use std::io::Write;
+
+    use super::{ordie, tabw};
+    use {SerializeError, TableSerializer};
+
+    struct Fruit {
+        name: &'static str,
+        count: u32,
+        note: Option<&'static str>,
+    }
+
+    impl Serialize for Fruit {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            let mut fruit = s.serialize_struct("Fruit", 3)?;
+            fruit.serialize_field("name", &self.name)?;
+            fruit.serialize_field("count", &self.count)?;
+            fruit.serialize_field("note", &self.note)?;
+            fruit.end()
+        }
+    }
+
+    fn fruit() -> Vec<Fruit> {
+        vec![
+            Fruit { name: "apples", count: 3, note: None },
+            Fruit { name: "bananas", count: 12, note: Some("ripe") },
+        ]
+    }
+
+    fn serialize<T: Serialize>(header: bool, value: &T) -> String {
+        let mut tw = tabw();
+        ordie(TableSerializer::new(&mut tw).header(header).serialize(value));
+        ordie(tw.flush());
+        ordie(String::from_utf8(tw.into_inner().unwrap()))
+    }
+
+    #[test]
+    fn test_structs() {
+        assert_eq!(serialize(false, &fruit()),
+                   "apples   3   \nbananas  12  ripe\n");
+    }
+
+    #[test]
+    fn test_structs_header() {
+        assert_eq!(serialize(true, &fruit()),
+                   "name     count  note\n\
+                    apples   3      \n\
+                    bananas  12     ripe\n");
+    }
+
+    #[test]
+    fn test_tuples() {
+        let rows = vec![("a", 1, true), ("xx", 22, false)];
+        assert_eq!(serialize(true, &rows), "a   1   true\nxx  22  false\n");
+    }
+
+    #[test]
+    fn test_escapes_tabs_and_newlines() {
+        let rows = vec![("a\tb", "c\nd")];
+        assert_eq!(serialize(false, &rows), "a\\tb  c\\nd\n");
+    }
+
+    #[test]
+    fn test_escapes_delimiters_and_backslashes() {
+        let mut tw = tabw().delimiters(b"\t,");
+        let rows = vec![("a,b", "c\\d\re")];
+        ordie(TableSerializer::new(&mut tw).serialize(&rows));
+        ordie(tw.flush());
+        assert_eq!(ordie(String::from_utf8(tw.into_inner().unwrap())),
+                   "a\\x2cb  c\\\\d\\re\n");
+    }
+
+    /// Undoes the escapes of `TableSerializer`.
+    fn unescape(cell: &str) -> String {
+        let mut unescaped = String::new();
+        let mut chars = cell.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                unescaped.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some('x') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    let byte = ordie(u8::from_str_radix(&hex, 16));
+                    unescaped.push(char::from(byte));
+                }
+                Some(c) => unescaped.push(c),
+                None => panic!("incomplete escape in {:?}", cell),
+            }
+        }
+        unescaped
+    }
+
+    #[test]
+    fn test_escapes_round_trip() {
+        let values =
+            ["a\tb", "c\nd", "e\r\nf", "g\\h", "i,j", "\\t", ",\\x2c"];
+        let mut tw = tabw().minwidth(0).padding(1).delimiters(b"\t,");
+        ordie(TableSerializer::new(&mut tw).serialize(&[values]));
+        ordie(tw.flush());
+        let written = ordie(String::from_utf8(tw.into_inner().unwrap()));
+        let cells: Vec<String> =
+            written.trim_end_matches('\n').split(' ').map(unescape).collect();
+        assert_eq!(cells, values);
+    }
+
+    #[test]
+    fn test_not_a_table() {
+        let mut tw = tabw();
+        let err = TableSerializer::new(&mut tw).serialize(&5).unwrap_err();
+        assert!(matches!(err, SerializeError::Message(_)));
+    }
+
+    #[test]
+    fn test_nested_cell() {
+        let mut tw = tabw();
+        let rows = vec![(1, vec![2, 3])];
+        let err = TableSerializer::new(&mut tw).serialize(&rows).unwrap_err();
+        assert!(matches!(err, SerializeError::Message(_)));
+    }
+}
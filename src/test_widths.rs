@@ -0,0 +1,34 @@
+// These only use the width computations, so that they also run without the
+// `std` feature.
+
+use {cell_widths, display_columns, Cell};
+
+#[test]
+fn test_ragged_rows_cell_widths() {
+    let cell = |width| Cell { start: 0, width, size: width };
+    let lines = vec![
+        vec![cell(1), cell(1), cell(1)],
+        vec![cell(4), cell(6)],
+    ];
+    assert_eq!(cell_widths(&lines, 0), vec![vec![4, 1], vec![4]]);
+}
+
+#[test]
+fn test_cell_from_buf() {
+    let cell = Cell::from_buf("abé".as_bytes(), 1, 3);
+    assert_eq!((cell.start, cell.width, cell.size), (1, 2, 3));
+}
+
+#[test]
+fn test_display_columns() {
+    assert_eq!(display_columns(b" hello, world~"), 14);
+    assert_eq!(display_columns(b"a\x00b\x7f"), 2);
+    assert_eq!(display_columns(b"\xFFab"), 3);
+    assert_eq!(display_columns("héllo".as_bytes()), 5);
+}
+
+#[test]
+#[cfg(feature = "ansi_formatting")]
+fn test_display_columns_ansi() {
+    assert_eq!(display_columns(b"\x1B[31mred\x1B[0m"), 3);
+}
@@ -0,0 +1,234 @@
+// Everything here only needs `core` and `alloc`, so that it is also
+// available without the `std` feature.
+
+use core::cmp;
+use core::str;
+
+#[cfg(feature = "ansi_formatting")]
+use alloc::borrow::Cow;
+#[cfg(feature = "ansi_formatting")]
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A single cell of a line of text, as used by `cell_widths`.
+///
+/// A cell doesn't hold its text. Instead, the text of many cells is kept in
+/// a single buffer, and a cell refers to the bytes
+/// `buf[start..start + size]` of it. This is what `TabWriter` does, and it
+/// makes it possible to build other renderers on top of `cell_widths`:
+///
+/// ```rust
+/// use tabwriter::{cell_widths, Cell};
+///
+/// let buf = b"abxyzc";
+/// let lines = vec![
+///     vec![Cell::from_buf(buf, 0, 1), Cell::from_buf(buf, 1, 1)],
+///     vec![Cell::from_buf(buf, 2, 3), Cell::from_buf(buf, 5, 1)],
+/// ];
+/// let widths = cell_widths(&lines, 0);
+/// assert_eq!(widths, vec![vec![3], vec![3]]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Cell {
+    /// The offset of the cell's text in the buffer that holds it.
+    pub start: usize,
+    /// The width of the cell's text in display columns, as returned by
+    /// `display_columns`.
+    pub width: usize,
+    /// The length of the cell's text in bytes.
+    pub size: usize,
+}
+
+impl Cell {
+    /// Create a cell for the bytes `buf[start..start + size]`, computing
+    /// its width with `display_columns`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the range is out of bounds for `buf`.
+    pub fn from_buf(buf: &[u8], start: usize, size: usize) -> Cell {
+        let mut cell = Cell { start, width: 0, size };
+        cell.update_width(buf);
+        cell
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn new(start: usize) -> Cell {
+        Cell { start, width: 0, size: 0 }
+    }
+
+    pub(crate) fn update_width(&mut self, buf: &[u8]) {
+        let end = self.start + self.size;
+        self.width = display_columns(&buf[self.start..end]);
+    }
+}
+
+/// Computes the width of every column of every line, in the same way that
+/// `TabWriter` does before writing them.
+///
+/// Only contiguous columns are aligned, and the last cell of each line is
+/// not part of any column. So the widths of a line have one entry fewer
+/// than the line has cells. Every width is at least `minwidth`, and does not
+/// include any padding.
+///
+/// Lines may have any number of cells. A column spans the contiguous lines
+/// that have a cell in it which is not their last cell, so in the example
+/// below, the last cell of the first line doesn't widen the second column
+/// of the second line, and the third line ends the first column.
+///
+/// ```rust
+/// use tabwriter::{cell_widths, Cell};
+///
+/// let cell = |width| Cell { start: 0, width, size: width };
+/// let lines = vec![
+///     vec![cell(1), cell(3)],
+///     vec![cell(4), cell(1), cell(1)],
+///     vec![cell(7)],
+/// ];
+/// assert_eq!(cell_widths(&lines, 2), vec![vec![4], vec![4, 2], vec![]]);
+/// ```
+pub fn cell_widths(lines: &[Vec<Cell>], minwidth: usize) -> Vec<Vec<usize>> {
+    let blank = vec![false; lines.len()];
+    let mut ws = vec![];
+    column_block_widths(&mut ws, lines, minwidth, &[], &blank);
+    ws
+}
+
+/// Like `cell_widths`, with a minimum width per column, except the widths
+/// are stored in `ws`, reusing its allocations. Lines for which `blank` is
+/// true are skipped over, so that they don't break contiguous columns.
+pub(crate) fn column_block_widths(
+    ws: &mut Vec<Vec<usize>>,
+    lines: &[Vec<Cell>],
+    minwidth: usize,
+    minwidths: &[Option<usize>],
+    blank: &[bool],
+) {
+    // Naively, this algorithm looks like it could be O(n^2m) where `n` is
+    // the number of lines and `m` is the number of contiguous columns.
+    //
+    // However, I claim that it is actually O(nm). That is, the width for
+    // every contiguous column is computed exactly once.
+    clear_widths(ws, lines.len());
+    for (i, iline) in lines.iter().enumerate() {
+        if iline.is_empty() {
+            continue
+        }
+        for col in ws[i].len()..(iline.len()-1) {
+            let mut width = minwidths.get(col).cloned().flatten()
+                                     .unwrap_or(minwidth);
+            let mut contig_count = 0;
+            for (line, &blank) in lines[i..].iter().zip(&blank[i..]) {
+                if blank {
+                    contig_count += 1;
+                    continue
+                }
+                if col + 1 >= line.len() { // ignores last column
+                    break
+                }
+                contig_count += 1;
+                width = cmp::max(width, line[col].width);
+            }
+            assert!(contig_count >= 1);
+            for w in &mut ws[i..(i+contig_count)] {
+                w.push(width);
+            }
+        }
+    }
+}
+
+/// Empties `ws` and resizes it to hold the widths of `n` lines, keeping
+/// the allocations of the lines that it already holds.
+pub(crate) fn clear_widths(ws: &mut Vec<Vec<usize>>, n: usize) {
+    ws.truncate(n);
+    for w in ws.iter_mut() {
+        w.clear();
+    }
+    ws.resize(n, vec![]);
+}
+
+/// Returns the number of columns that the given text occupies on a display.
+///
+/// If `bytes` is valid UTF-8, then the width of every character is taken
+/// into account, e.g., East Asian wide characters use two columns. With the
+/// `ansi_formatting` feature, ANSI escape sequences use no columns.
+/// Otherwise, every byte is assumed to use one column.
+///
+/// ```rust
+/// assert_eq!(tabwriter::display_columns("abc".as_bytes()), 3);
+/// assert_eq!(tabwriter::display_columns(b"\xFF\xFF"), 2);
+/// ```
+pub fn display_columns(bytes: &[u8]) -> usize {
+    // Every printable ASCII character uses exactly one column, so there is
+    // no need to validate or decode the common case. Control characters,
+    // including the start of any ANSI escape sequence, take the slow path.
+    if bytes.iter().all(|b| (b' '..=b'~').contains(b)) {
+        return bytes.len();
+    }
+    // If we have a Unicode string, then attempt to guess the number of
+    // *display* columns used.
+    match str::from_utf8(bytes) {
+        Err(_) => bytes.len(),
+        #[cfg(feature = "ansi_formatting")]
+        Ok(s) => str_columns(&strip_formatting(s)),
+        #[cfg(not(feature = "ansi_formatting"))]
+        Ok(s) => str_columns(s),
+    }
+}
+
+/// Returns the number of display columns used by `s`.
+pub(crate) fn str_columns(s: &str) -> usize {
+    s.chars().map(char_columns).sum()
+}
+
+/// Returns the number of display columns used by `c`, according to its
+/// East Asian Width property.
+#[cfg(feature = "unicode-width")]
+pub(crate) fn char_columns(c: char) -> usize {
+    use unicode_width::UnicodeWidthChar;
+
+    UnicodeWidthChar::width(c).unwrap_or(0)
+}
+
+/// Returns the number of display columns used by `c`, assuming that every
+/// character other than a control character uses exactly one column.
+#[cfg(not(feature = "unicode-width"))]
+pub(crate) fn char_columns(c: char) -> usize {
+    if c.is_control() { 0 } else { 1 }
+}
+
+/// Removes ANSI CSI escape sequences (such as the SGR sequences used for
+/// colors) from `input`.
+///
+/// A sequence is `ESC [`, followed by any number of parameter and
+/// intermediate bytes, followed by a final byte. A sequence that is cut off
+/// before its final byte is removed up to the end of `input`.
+#[cfg(feature = "ansi_formatting")]
+pub(crate) fn strip_formatting(input: &str) -> Cow<'_, str> {
+    if !input.contains("\x1B[") {
+        return Cow::Borrowed(input);
+    }
+    let mut stripped = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1B' || chars.peek() != Some(&'[') {
+            stripped.push(c);
+            continue;
+        }
+        chars.next();
+        // Parameter bytes are in 0x30-0x3F and intermediate bytes are in
+        // 0x20-0x2F. Anything else ends the sequence, but only bytes in
+        // 0x40-0x7E are a valid final byte that belongs to it.
+        while let Some(&c) = chars.peek() {
+            if ('\x20'..='\x3F').contains(&c) {
+                chars.next();
+                continue;
+            }
+            if ('\x40'..='\x7E').contains(&c) {
+                chars.next();
+            }
+            break;
+        }
+    }
+    Cow::Owned(stripped)
+}
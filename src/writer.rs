@@ -0,0 +1,2696 @@
+// Everything that needs `std`, i.e., `TabWriter` and everything built on
+// top of it. This module is only compiled with the `std` feature.
+
+use std::cmp;
+use std::error;
+use std::fmt;
+use std::io::{self, Write};
+use std::iter::FromIterator;
+use std::mem;
+use std::slice;
+use std::str;
+use std::string;
+use std::thread;
+
+use widths::{cell_widths, char_columns, clear_widths, column_block_widths,
+             display_columns, Cell};
+
+pub use self::aligner::{AlignedCell, TabAligner};
+pub use self::boxed::{BoxStyle, BoxTableWriter};
+pub use self::csv::CsvTableWriter;
+pub use self::html::HtmlTableWriter;
+pub use self::latex::LatexTabularWriter;
+pub use self::markdown::MarkdownTableWriter;
+pub use self::reader::TabReader;
+pub use self::rst::RstTableWriter;
+#[cfg(feature = "serde")]
+pub use self::ser::{SerializeError, TableMap, TableSerializer};
+
+// These come first, so that their macros can be used by the other modules.
+#[macro_use]
+mod table;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[macro_use]
+mod async_write;
+
+mod aligner;
+#[cfg(feature = "futures-io")]
+mod async_futures;
+#[cfg(feature = "tokio")]
+mod async_tokio;
+mod boxed;
+mod csv;
+mod html;
+mod latex;
+mod markdown;
+mod reader;
+mod rst;
+#[cfg(feature = "serde")]
+mod ser;
+
+#[cfg(test)]
+pub(crate) use self::csv::quote;
+
+/// TabWriter wraps an arbitrary writer and aligns tabbed output.
+///
+/// Elastic tabstops work by aligning *contiguous* tabbed delimited fields
+/// known as *column blocks*. When a line appears that breaks all contiguous
+/// blocks, all buffered output will be flushed to the underlying writer.
+/// Otherwise, output will stay buffered until `flush` is explicitly called.
+///
+/// Note that such a line is the only thing that can guarantee that the
+/// alignment of the lines before it is final, since any later line may
+/// widen the first column. So every alignment group is written as soon as
+/// it is known to be complete, and the lines that are still buffered always
+/// belong to a group that may yet change.
+///
+/// A `TabWriter` can be cloned if its writer can be, which copies all
+/// buffered text along with the writer. This may be used to checkpoint the
+/// state of the aligner before a speculative write.
+///
+/// # Buffering
+///
+/// A `TabWriter` buffers text until it can be aligned, so wrapping it in an
+/// `io::BufWriter` adds nothing. Aligned text is written to the underlying
+/// writer one line at a time, though, with a few small writes per line. So
+/// when the underlying writer is slow to write to, e.g., a file or stdout,
+/// it should be wrapped in an `io::BufWriter` instead, which
+/// `TabWriter::new_buffered` does:
+///
+/// ```rust
+/// use std::io::{self, Write};
+/// use tabwriter::TabWriter;
+///
+/// # fn main() -> io::Result<()> {
+/// let mut tw = TabWriter::new_buffered(io::stdout());
+/// write!(&mut tw, "a\tb\nxx\tyy\n")?;
+/// // This writes the aligned text to the `BufWriter`, and then flushes it.
+/// tw.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Text is aligned the same way with either order, since the `TabWriter`
+/// sees the same text. But a `BufWriter<TabWriter<W>>` only passes text on
+/// in chunks of its own choosing, so alignment groups would be written
+/// later than necessary, while the small writes of the `TabWriter` would
+/// still not be buffered.
+///
+/// Text that is still buffered when a `TabWriter` is dropped is lost, so
+/// `flush` must be called before. To catch a missing flush, dropping a
+/// `TabWriter` with buffered text panics in debug builds, unless the last
+/// attempt to write the text failed. To discard buffered text on purpose,
+/// call `reset` first. A clone, e.g., a checkpoint that may never be used,
+/// is only checked once more text is written to it.
+///
+/// The writer may also be a trait object, e.g., a `Box<dyn io::Write>`
+/// that writes to either a file or stdout. Since the underlying writer is
+/// only called once per flushed group, with the aligned text, dynamic
+/// dispatch adds no overhead to alignment itself. Note that a
+/// `TabWriter<Box<dyn io::Write>>` only has the capabilities of the trait
+/// object, so it does not implement `io::Seek` or `Debug`, and can't be
+/// cloned.
+#[derive(Clone)]
+pub struct TabWriter<W> {
+    w: W,
+    buf: io::Cursor<Vec<u8>>,
+    lines: Vec<Vec<Cell>>,
+    curcell: Cell,
+    opts: Options,
+    stats: AlignmentResult,
+    /// Aligned lines that were committed by the lookahead window, but that
+    /// have not been written yet.
+    committed: Vec<u8>,
+    /// The widths used for the last committed line of the current group.
+    committed_widths: Vec<usize>,
+    /// Whether the current group was followed by blank lines that have not
+    /// been written yet. See `ignore_trailing_blank_lines`.
+    group_end_deferred: bool,
+    /// Whether the comment prefix has been found on the current line. See
+    /// `comment_mode`.
+    comment_found: bool,
+    /// Whether the first buffered line was written by `write_header`.
+    has_header: bool,
+    /// Whether the byte order mark has been written. See `utf8_bom`.
+    bom_written: bool,
+    /// Catches text that was never flushed, in debug builds.
+    flush_check: FlushCheck,
+    /// Buffers used while aligning, kept so that their allocations are
+    /// reused by every flush.
+    scratch: Scratch,
+    /// The largest width of every column of the last group written. See
+    /// `column_widths_last_flush`.
+    flushed_widths: Vec<usize>,
+    /// Aligned text that has not been written to an asynchronous writer.
+    #[cfg(any(feature = "tokio", feature = "futures-io"))]
+    pending: Vec<u8>,
+}
+
+/// A builder for configuring a `TabWriter`.
+///
+/// Unlike the configuration methods on `TabWriter` itself, a builder is not
+/// tied to a particular writer, so the same configuration may be used to
+/// build any number of `TabWriter`s:
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::TabWriterBuilder;
+///
+/// let builder = TabWriterBuilder::new().padding(1);
+/// for table in &["a\tb\nxx\tyy\n", "abc\td\n"] {
+///     let mut tw = builder.build(vec![]);
+///     tw.write_all(table.as_bytes()).unwrap();
+///     tw.flush().unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TabWriterBuilder(Options);
+
+#[derive(Clone, Debug)]
+struct Options {
+    minwidth: usize,
+    minwidths: Vec<Option<usize>>,
+    padding: usize,
+    paddings: Vec<Option<usize>>,
+    alignments: Vec<Alignment>,
+    row_separator: RowSeparator,
+    delimiters: Vec<u8>,
+    max_width: Option<usize>,
+    max_widths: Vec<Option<usize>>,
+    truncation_marker: String,
+    terminal_width: Option<usize>,
+    fill: char,
+    fills: Vec<Option<char>>,
+    separator: String,
+    single_group: bool,
+    preserve_indent: bool,
+    strip_trailing: bool,
+    group_separator: GroupSeparator,
+    flush_interval: Option<usize>,
+    lookahead: Option<usize>,
+    normalize_indent: bool,
+    ignore_trailing_blank_lines: bool,
+    comment_prefix: Option<String>,
+    utf8_bom: bool,
+    line_prefix: String,
+}
+
+impl Options {
+    /// Return the alignment of the column at index `col`.
+    fn alignment(&self, col: usize) -> Alignment {
+        self.alignments.get(col).cloned().unwrap_or(Alignment::Left)
+    }
+
+    /// Return the padding after the column at index `col`.
+    fn column_padding_of(&self, col: usize) -> usize {
+        let padding = self.paddings.get(col).cloned().flatten();
+        padding.unwrap_or(self.padding)
+    }
+
+    /// Return the number of columns between the cell at index `col` and the
+    /// next one, i.e., its padding and the separator.
+    fn column_gap_of(&self, col: usize) -> usize {
+        let padding = self.column_padding_of(col);
+        if self.separator.is_empty() {
+            padding
+        } else {
+            padding + display_columns(self.separator.as_bytes()) + padding
+        }
+    }
+
+    /// Return the fill character of the column at index `col`.
+    fn fill_of(&self, col: usize) -> char {
+        self.fills.get(col).cloned().flatten().unwrap_or(self.fill)
+    }
+
+    /// Return the maximum width of the column at index `col`, if any.
+    fn max_width_of(&self, col: usize) -> Option<usize> {
+        self.max_widths.get(col).cloned().flatten().or(self.max_width)
+    }
+
+    /// Replaces all settings of the column at index `col`.
+    fn set_column_config(&mut self, col: usize, config: Column) {
+        let fill = config.fill_char.map(check_fill_char);
+        let align = config.alignment.unwrap_or(Alignment::Left);
+        set_column(&mut self.alignments, col, align, Alignment::Left);
+        set_column(&mut self.minwidths, col, config.minwidth, None);
+        set_column(&mut self.paddings, col, config.padding, None);
+        set_column(&mut self.fills, col, fill, None);
+        set_column(&mut self.max_widths, col, config.max_width, None);
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            minwidth: 2,
+            minwidths: vec![],
+            padding: 2,
+            paddings: vec![],
+            alignments: vec![],
+            row_separator: RowSeparator::Newline,
+            delimiters: delimiter_set(b"\t"),
+            max_width: None,
+            max_widths: vec![],
+            truncation_marker: "...".to_string(),
+            terminal_width: None,
+            fill: ' ',
+            fills: vec![],
+            separator: String::new(),
+            single_group: false,
+            preserve_indent: false,
+            strip_trailing: false,
+            group_separator: GroupSeparator::SingleCell,
+            flush_interval: None,
+            lookahead: None,
+            normalize_indent: false,
+            ignore_trailing_blank_lines: false,
+            comment_prefix: None,
+            utf8_bom: false,
+            line_prefix: String::new(),
+        }
+    }
+}
+
+/// Statistics about aligned text, as returned by
+/// `TabWriter::flush_with_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AlignmentResult {
+    /// The number of lines written.
+    pub lines: usize,
+    /// The number of alignment groups written.
+    pub groups: usize,
+    /// The largest number of cells in any line.
+    pub max_columns: usize,
+    /// The number of bytes written to the underlying writer.
+    pub bytes: usize,
+}
+
+impl AlignmentResult {
+    /// Adds the statistics of another group of lines to these.
+    fn add(&mut self, other: AlignmentResult) {
+        self.lines += other.lines;
+        self.groups += other.groups;
+        self.max_columns = cmp::max(self.max_columns, other.max_columns);
+        self.bytes += other.bytes;
+    }
+}
+
+/// The settings of a single column, for use with `TabWriter::add_column`.
+///
+/// Every setting that is `None` falls back to the setting for all columns,
+/// so `Column::default()` configures a column like any other.
+///
+/// ```rust
+/// use tabwriter::{Alignment, Column, TabWriter};
+///
+/// let mut tw = TabWriter::new_string().add_column(0, Column {
+///     alignment: Some(Alignment::Right),
+///     fill_char: Some('.'),
+///     ..Column::default()
+/// });
+/// tw.write_row(&["a", "b"]).unwrap();
+/// tw.write_row(&["xyz", "c"]).unwrap();
+/// assert_eq!(tw.into_string().unwrap(), "..a..b\nxyz..c\n");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Column {
+    /// See `TabWriter::column_alignment`. Columns are left aligned by
+    /// default.
+    pub alignment: Option<Alignment>,
+    /// See `TabWriter::column_minwidth`.
+    pub minwidth: Option<usize>,
+    /// See `TabWriter::column_padding`.
+    pub padding: Option<usize>,
+    /// The character used to pad cells in this column. See
+    /// `TabWriter::fill_char`.
+    pub fill_char: Option<char>,
+    /// The maximum width of cells in this column. See
+    /// `TabWriter::max_column_width`.
+    pub max_width: Option<usize>,
+}
+
+/// The alignment of cell content within a column.
+///
+/// Padding is added after the content of a left aligned cell, before the
+/// content of a right aligned cell and split evenly on both sides of a
+/// center aligned cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    /// Content is aligned to the left of its column. This is the default.
+    #[default]
+    Left,
+    /// Content is aligned to the right of its column.
+    Right,
+    /// Content is centered in its column. If the padding cannot be split
+    /// evenly, the extra space goes on the right.
+    Center,
+}
+
+impl Alignment {
+    /// Splits `extra` columns of padding into the padding before and after
+    /// content with this alignment.
+    fn split(self, extra: usize) -> (usize, usize) {
+        match self {
+            Alignment::Left => (0, extra),
+            Alignment::Right => (extra, 0),
+            Alignment::Center => (extra / 2, extra - extra / 2),
+        }
+    }
+}
+
+/// What ends an alignment group, causing all buffered text to be written.
+///
+/// Columns are only aligned within a group. Groups are ignored when
+/// `single_group` is enabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum GroupSeparator {
+    /// A line with a single cell, i.e., a line without any delimiters, ends
+    /// the group. This is the default.
+    #[default]
+    SingleCell,
+    /// A line with a cell that contains the given text ends the group. The
+    /// lines before it are written first, and then the separator line is
+    /// written on its own, so a separator without delimiters is written
+    /// unchanged.
+    Contains(String),
+    /// Every group has the given number of lines.
+    LineCount(usize),
+}
+
+/// The line terminator written between lines of output.
+///
+/// Note that input is always accepted with either `\n` or `\r\n` line
+/// endings, regardless of this setting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// A single line feed, `\n`. This is the default.
+    #[default]
+    Lf,
+    /// A carriage return followed by a line feed, `\r\n`.
+    CrLf,
+}
+
+/// The text written between rows of output, for use with
+/// `TabWriter::row_separator`.
+///
+/// This generalizes `LineEnding`. Note that input is always accepted with
+/// either `\n` or `\r\n` line endings, regardless of this setting.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RowSeparator {
+    /// A single line feed, `\n`. This is the default.
+    #[default]
+    Newline,
+    /// A carriage return followed by a line feed, `\r\n`.
+    CrLf,
+    /// The given text, e.g., `"\n\n"` to write an empty line after every
+    /// row.
+    Custom(String),
+    /// Nothing at all, so that rows are written right after each other, for
+    /// output that gets its line endings added elsewhere.
+    None,
+}
+
+impl RowSeparator {
+    fn as_bytes(&self) -> &[u8] {
+        match *self {
+            RowSeparator::Newline => b"\n",
+            RowSeparator::CrLf => b"\r\n",
+            RowSeparator::Custom(ref sep) => sep.as_bytes(),
+            RowSeparator::None => b"",
+        }
+    }
+}
+
+impl From<LineEnding> for RowSeparator {
+    fn from(line_ending: LineEnding) -> RowSeparator {
+        match line_ending {
+            LineEnding::Lf => RowSeparator::Newline,
+            LineEnding::CrLf => RowSeparator::CrLf,
+        }
+    }
+}
+
+impl<W> TabWriter<W> {
+    /// Create a new `TabWriter` from an existing `Writer`.
+    ///
+    /// All output written to `Writer` is passed through `TabWriter`.
+    /// Contiguous column blocks indicated by tabs are aligned.
+    ///
+    /// Note that `flush` must be called to guarantee that `TabWriter` will
+    /// write to the given writer.
+    pub fn new(w: W) -> TabWriter<W> {
+        TabWriterBuilder::new().build(w)
+    }
+
+    /// Create a new `TabWriter` whose internal buffer can hold at least
+    /// `capacity` bytes of text before it has to grow.
+    ///
+    /// The buffer keeps its capacity when it is flushed, so this is useful
+    /// when the size of each alignment group is roughly known in advance.
+    /// `new` starts with a capacity of `1024` bytes.
+    pub fn with_capacity(w: W, capacity: usize) -> TabWriter<W> {
+        let mut tw = TabWriter::new(w);
+        tw.buf = io::Cursor::new(Vec::with_capacity(capacity));
+        tw
+    }
+
+    /// Returns the capacity of the internal buffer, so that the tests can
+    /// check that it is kept.
+    #[cfg(test)]
+    pub(crate) fn buf_capacity(&self) -> usize {
+        self.buf.get_ref().capacity()
+    }
+
+    /// Applies a `TabWriterBuilder` setting to the options of this
+    /// `TabWriter`, so that every setting is only implemented once.
+    fn configure<F>(mut self, f: F) -> TabWriter<W>
+        where F: FnOnce(TabWriterBuilder) -> TabWriterBuilder
+    {
+        let opts = mem::take(&mut self.opts);
+        self.opts = f(TabWriterBuilder(opts)).0;
+        self
+    }
+
+    /// Set the minimum width of each column. That is, all columns will have
+    /// *at least* the size given here. If a column is smaller than `minwidth`,
+    /// then it is passed with spaces.
+    ///
+    /// The default minimum width is `2`.
+    pub fn minwidth(self, minwidth: usize) -> TabWriter<W> {
+        self.configure(|b| b.minwidth(minwidth))
+    }
+
+    /// Set the minimum width of the column at index `col`, where the first
+    /// column has index `0`. This takes precedence over `minwidth` for that
+    /// column.
+    ///
+    /// Columns without an explicit minimum width use `minwidth`.
+    pub fn column_minwidth(self, col: usize, width: usize) -> TabWriter<W> {
+        self.configure(|b| b.column_minwidth(col, width))
+    }
+
+    /// Set the padding between columns. All columns will be separated by
+    /// *at least* the number of spaces indicated by `padding`. If `padding`
+    /// is zero, then columns may run up against each other without any
+    /// separation.
+    ///
+    /// The default padding is `2`.
+    pub fn padding(self, padding: usize) -> TabWriter<W> {
+        self.configure(|b| b.padding(padding))
+    }
+
+    /// Set the padding after the column at index `col`, where the first
+    /// column has index `0`. This takes precedence over `padding` for that
+    /// column.
+    ///
+    /// Padding is added on top of the column's width, so a column with a
+    /// minimum width of `5` and a padding of `2` is always at least `7`
+    /// columns wide.
+    ///
+    /// Columns without an explicit padding use `padding`.
+    pub fn column_padding(
+        self,
+        col: usize,
+        padding: usize,
+    ) -> TabWriter<W> {
+        self.configure(|b| b.column_padding(col, padding))
+    }
+
+    /// Set the alignment of the column at index `col`, where the first
+    /// column has index `0`.
+    ///
+    /// Note that the last cell of each line is never padded, so its
+    /// alignment has no effect.
+    ///
+    /// Columns without an explicit alignment are left aligned.
+    pub fn column_alignment(
+        self,
+        col: usize,
+        align: Alignment,
+    ) -> TabWriter<W> {
+        self.configure(|b| b.column_alignment(col, align))
+    }
+
+    /// Set the maximum width of every cell. Cells that are wider are
+    /// truncated and end with the truncation marker, such that they are
+    /// exactly `max_width` columns wide. (A truncated cell may be one column
+    /// narrower when a wide character would otherwise be split.)
+    ///
+    /// If the truncation marker does not fit in `max_width` columns, then
+    /// cells are truncated without a marker.
+    ///
+    /// By default, cells are never truncated.
+    pub fn max_column_width(self, max_width: usize) -> TabWriter<W> {
+        self.configure(|b| b.max_column_width(max_width))
+    }
+
+    /// Set the text that ends a cell truncated by `max_column_width` or
+    /// `terminal_width`.
+    ///
+    /// The default truncation marker is `...`.
+    pub fn truncation_marker(self, marker: &str) -> TabWriter<W> {
+        self.configure(|b| b.truncation_marker(marker))
+    }
+
+    /// Set the width of the terminal that the text is written to, so that
+    /// no aligned line is wider than `width` columns.
+    ///
+    /// When a line of an alignment group would be wider, the widest columns
+    /// of the group are narrowed to a common width, just enough to make every
+    /// line fit, and cells that are wider than their column are truncated
+    /// with the truncation marker. The last cell of a line is truncated to
+    /// whatever space is left. Since all columns are narrowed in the same
+    /// way, the lines stay aligned.
+    ///
+    /// Every column keeps at least one column of text, so a line with many
+    /// cells may still be wider than `width`.
+    ///
+    /// Note that lines written early because of `lookahead` are fitted on
+    /// their own.
+    ///
+    /// By default, lines may be arbitrarily wide.
+    pub fn terminal_width(self, width: usize) -> TabWriter<W> {
+        self.configure(|b| b.terminal_width(width))
+    }
+
+    /// Set the character used to pad cells.
+    ///
+    /// The default fill character is a space.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `fill` does not occupy exactly one column.
+    pub fn fill_char(self, fill: char) -> TabWriter<W> {
+        self.configure(|b| b.fill_char(fill))
+    }
+
+    /// Set a separator that is written between adjacent columns.
+    ///
+    /// When a separator is set, the padding of a column is written both
+    /// before and after the separator. For example, a separator of `|` with
+    /// a padding of `1` separates columns with ` | `.
+    ///
+    /// By default, there is no separator.
+    pub fn column_separator(self, separator: &str) -> TabWriter<W> {
+        self.configure(|b| b.column_separator(separator))
+    }
+
+    /// When enabled, all text written between flushes is treated as a
+    /// single alignment group. That is, every column is aligned across all
+    /// lines, even if it is not contiguous.
+    ///
+    /// This also means that a line without any tabs never causes buffered
+    /// text to be written, so `flush` must be called to write anything.
+    ///
+    /// # Warning
+    ///
+    /// All text is held in memory until `flush` is called, so a long
+    /// running stream that is never flushed uses an unbounded amount of
+    /// memory.
+    ///
+    /// This is disabled by default.
+    pub fn single_group(self, yes: bool) -> TabWriter<W> {
+        self.configure(|b| b.single_group(yes))
+    }
+
+    /// When enabled, lines that contain nothing but whitespace, such as
+    /// blank or indentation only lines in a block of code, do not end the
+    /// current alignment group. Such lines are written unchanged and the
+    /// lines around them are aligned as if they were not there.
+    ///
+    /// This is disabled by default.
+    pub fn preserve_indent(self, yes: bool) -> TabWriter<W> {
+        self.configure(|b| b.preserve_indent(yes))
+    }
+
+    /// When enabled, no line of output ends with whitespace. Spaces and tabs
+    /// at the end of the last cell are removed, and cells that are only
+    /// followed by empty cells aren't padded, whatever the fill character.
+    ///
+    /// This is disabled by default.
+    pub fn strip_trailing_whitespace(self, yes: bool) -> TabWriter<W> {
+        self.configure(|b| b.strip_trailing_whitespace(yes))
+    }
+
+    /// Set what ends an alignment group.
+    ///
+    /// The default is `GroupSeparator::SingleCell`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the text of `GroupSeparator::Contains` is empty.
+    pub fn group_separator(self, sep: GroupSeparator) -> TabWriter<W> {
+        self.configure(|b| b.group_separator(sep))
+    }
+
+    /// Write all buffered text whenever `n` lines have been buffered, in
+    /// addition to whenever an alignment group ends.
+    ///
+    /// This makes output appear promptly, at the cost of aligning columns
+    /// separately in each chunk of `n` lines. This applies even when
+    /// `single_group` is enabled.
+    ///
+    /// By default, there is no limit on the number of buffered lines.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `n` is zero.
+    pub fn flush_every(self, n: usize) -> TabWriter<W> {
+        self.configure(|b| b.flush_every(n))
+    }
+
+    /// Limit every alignment group to a window of `n` buffered lines.
+    ///
+    /// Whenever a line would be buffered beyond the window, the oldest line
+    /// is written using the widths known from the lines in the window. Later
+    /// lines in the same group are never aligned narrower than the lines
+    /// already written, but a line that is wider than everything in the
+    /// window before it will not line up with them. In exchange, memory use
+    /// is bounded by the window rather than by the size of the group.
+    ///
+    /// This has no effect when `single_group` is enabled.
+    ///
+    /// By default, there is no window and groups are aligned as a whole.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `n` is zero.
+    pub fn lookahead(self, n: usize) -> TabWriter<W> {
+        self.configure(|b| b.lookahead(n))
+    }
+
+    /// When enabled, the leading whitespace that all lines of an alignment
+    /// group have in common is removed before aligning them, and added back
+    /// afterwards. Lines that contain nothing but whitespace are ignored.
+    ///
+    /// This means that the first column is measured from the common
+    /// indentation, so e.g. `column_minwidth` applies to the text after it.
+    ///
+    /// Note that lines written early because of `lookahead` are aligned
+    /// with their indentation.
+    ///
+    /// This is disabled by default.
+    pub fn normalize_indent(self, yes: bool) -> TabWriter<W> {
+        self.configure(|b| b.normalize_indent(yes))
+    }
+
+    /// When enabled, a blank line that ends an alignment group doesn't cause
+    /// the group to be written right away. Instead, the group and the blank
+    /// lines after it are written once a line that isn't blank follows them,
+    /// or when `flush` is called.
+    ///
+    /// Blank lines still end groups, so the text is aligned in exactly the
+    /// same way. For example, `"a\tb\n\nc\td\n"` is always aligned as two
+    /// separate groups. This only means that blank lines at the end of the
+    /// text written so far never write the group before them early.
+    ///
+    /// This is disabled by default.
+    pub fn ignore_trailing_blank_lines(self, yes: bool) -> TabWriter<W> {
+        self.configure(|b| b.ignore_trailing_blank_lines(yes))
+    }
+
+    /// When set, the first occurrence of `prefix` on every line ends a cell,
+    /// as if a delimiter had been written right before it. This makes it
+    /// possible to align the comments of source code without adding tabs:
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use tabwriter::TabWriter;
+    ///
+    /// let mut tw = TabWriter::new(vec![]).comment_mode("//");
+    /// write!(&mut tw, "let x = 1; // one\nx += 41; // the answer\n")
+    ///     .unwrap();
+    ///
+    /// let written = String::from_utf8(tw.into_inner().unwrap()).unwrap();
+    /// assert_eq!(written, "\
+    /// let x = 1;  // one
+    /// x += 41;    // the answer
+    /// ");
+    /// ```
+    ///
+    /// The prefix stays at the start of the next cell, and the spaces before
+    /// it are removed. Lines without the prefix, and lines that start with
+    /// it (after any indentation), are left as they are.
+    ///
+    /// Note that the text is not parsed in any way, so a prefix inside e.g.
+    /// a string literal is treated like any other.
+    ///
+    /// By default, there is no comment prefix.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `prefix` is empty or contains a newline.
+    pub fn comment_mode(self, prefix: &str) -> TabWriter<W> {
+        self.configure(|b| b.comment_mode(prefix))
+    }
+
+    /// When enabled, the UTF-8 byte order mark `EF BB BF` is written before
+    /// the first text written to the underlying writer, which some programs,
+    /// e.g., Microsoft Excel, need to detect UTF-8 text.
+    ///
+    /// The byte order mark is only written once, no matter how often this
+    /// `TabWriter` is flushed, and nothing is written if there is no text.
+    /// `take_inner` starts over, so the new writer gets one too.
+    ///
+    /// This is disabled by default.
+    pub fn utf8_bom(self, yes: bool) -> TabWriter<W> {
+        self.configure(|b| b.utf8_bom(yes))
+    }
+
+    /// Set the text written at the start of every line, e.g., to indent the
+    /// aligned text when it is embedded in other text.
+    ///
+    /// The prefix is added after aligning, so it doesn't affect the widths
+    /// of any columns:
+    ///
+    /// ```rust
+    /// use tabwriter::TabWriter;
+    ///
+    /// let tw = TabWriter::new_string().line_prefix("> ");
+    /// let aligned = tw.process("a\tb\nxx\tyy\n").unwrap();
+    /// assert_eq!(aligned, "> a   b\n> xx  yy\n");
+    /// ```
+    ///
+    /// By default, there is no prefix.
+    pub fn line_prefix(self, prefix: &str) -> TabWriter<W> {
+        self.configure(|b| b.line_prefix(prefix))
+    }
+
+    /// Set the line ending used when writing lines to the underlying writer.
+    ///
+    /// This is a shorthand for `row_separator` with the matching separator.
+    ///
+    /// The default line ending is `LineEnding::Lf`.
+    pub fn line_ending(self, line_ending: LineEnding) -> TabWriter<W> {
+        self.configure(|b| b.line_ending(line_ending))
+    }
+
+    /// Set the text written between rows, i.e., in place of every line
+    /// ending of the input.
+    ///
+    /// ```rust
+    /// use tabwriter::{RowSeparator, TabWriter};
+    ///
+    /// let tw = TabWriter::new_string()
+    ///     .row_separator(RowSeparator::Custom(" | ".to_string()));
+    /// let aligned = tw.process("a\tb\nxx\tyy").unwrap();
+    /// assert_eq!(aligned, "a   b | xx  yy");
+    /// ```
+    ///
+    /// The default row separator is `RowSeparator::Newline`.
+    pub fn row_separator(self, sep: RowSeparator) -> TabWriter<W> {
+        self.configure(|b| b.row_separator(sep))
+    }
+
+    /// Set the byte that separates cells.
+    ///
+    /// When a delimiter other than `\t` is set, tab bytes are treated like
+    /// any other cell content.
+    ///
+    /// The default delimiter is `\t`.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `delimiter` is not ASCII or if it is `\n`.
+    pub fn delimiter(self, delimiter: u8) -> TabWriter<W> {
+        self.delimiters(&[delimiter])
+    }
+
+    /// Set the bytes that separate cells. Any one of the given bytes ends
+    /// the current cell.
+    ///
+    /// This replaces any previously set delimiters.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `delimiters` is empty, or if any of its bytes is not
+    /// ASCII or is `\n`.
+    pub fn delimiters(self, delimiters: &[u8]) -> TabWriter<W> {
+        self.configure(|b| b.delimiters(delimiters))
+    }
+
+    /// Set all settings of the column at index `col`, where the first
+    /// column has index `0`. This replaces any settings made for that
+    /// column with the other `column_*` methods.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the fill character of `config` does not occupy
+    /// exactly one column.
+    pub fn add_column(self, col: usize, config: Column) -> TabWriter<W> {
+        self.configure(|b| b.add_column(col, config))
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.w
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// # Warning
+    ///
+    /// It is inadvisable to directly write to the underlying writer. Any
+    /// text still buffered in this `TabWriter` will be written after it,
+    /// which will likely corrupt the alignment.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.w
+    }
+
+    /// Returns the number of lines that are buffered, including the current
+    /// line if any text has been written to it.
+    pub fn lines_count(&self) -> usize {
+        let complete = self.lines.len() - 1;
+        if self.curline_started() { complete + 1 } else { complete }
+    }
+
+    /// Returns the largest number of cells in any buffered line, including
+    /// the current line.
+    pub fn columns_count(&self) -> usize {
+        let complete = &self.lines[..self.lines.len() - 1];
+        let current = if self.curline_started() {
+            self.curline().len() + 1
+        } else {
+            0
+        };
+        complete.iter().map(|line| line.len()).fold(current, cmp::max)
+    }
+
+    /// Returns the largest width of every column of the lines that were
+    /// written by the last flush, like `flush_and_widths`.
+    ///
+    /// This includes flushes that happen automatically at the end of an
+    /// alignment group, so after writing the last group of a table and
+    /// calling `flush`, these are the widths of that group. For example,
+    /// a header row can be aligned to a table that is written separately:
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use tabwriter::TabWriter;
+    ///
+    /// let mut tw = TabWriter::new(vec![]).padding(1);
+    /// write!(&mut tw, "apples\t3\tred\nfigs\t12\tblue\n").unwrap();
+    /// tw.flush().unwrap();
+    /// assert_eq!(tw.column_widths_last_flush(), &[6, 2]);
+    ///
+    /// let ws = tw.column_widths_last_flush();
+    /// let header = format!("{:w0$} {:w1$} {}", "name", "n", "color",
+    ///                      w0 = ws[0], w1 = ws[1]);
+    /// assert_eq!(header, "name   n  color");
+    /// ```
+    pub fn column_widths_last_flush(&self) -> &[usize] {
+        &self.flushed_widths
+    }
+
+    /// Returns the number of bytes of text that are buffered.
+    ///
+    /// This is the raw text of all buffered cells, including the cell that
+    /// is currently being written. Delimiters and line endings are not
+    /// buffered, so they are not counted.
+    pub fn buffered_bytes(&self) -> usize {
+        self.buf.get_ref().len()
+    }
+
+    /// Resets the state of the aligner. Once the aligner is reset, all future
+    /// writes will start producing a new alignment.
+    ///
+    /// Any text that is still buffered is discarded without being written
+    /// to the underlying writer. Use `flush` to write buffered text instead.
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use tabwriter::TabWriter;
+    ///
+    /// let mut tw = TabWriter::new_string();
+    /// write!(&mut tw, "a very long cell\tthat was a mistake").unwrap();
+    /// tw.reset();
+    /// write!(&mut tw, "a\tb\n").unwrap();
+    /// assert_eq!(tw.into_string().unwrap(), "a   b\n");
+    /// ```
+    pub fn reset(&mut self) {
+        self.buf.get_mut().clear();
+        self.buf.set_position(0);
+        // The allocations of the first line are kept, so that writing one
+        // line at a time, e.g., lines without any cells, allocates nothing.
+        self.lines.truncate(1);
+        match self.lines.first_mut() {
+            Some(line) => line.clear(),
+            None => self.lines.push(vec!()),
+        }
+        self.curcell = Cell::new(0);
+        self.committed_widths.clear();
+        self.group_end_deferred = false;
+        self.comment_found = false;
+        self.has_header = false;
+        self.flush_check.unflushed = false;
+    }
+
+    /// Adds the bytes received into the buffer and updates the size of
+    /// the current cell.
+    fn add_bytes(&mut self, bytes: &[u8]) {
+        self.flush_check.unflushed |= !bytes.is_empty();
+        self.curcell.size += bytes.len();
+        let _ = self.buf.write_all(bytes); // cannot fail
+    }
+
+    /// Adds the bytes received like `add_bytes`, and splits the current cell
+    /// right before the comment prefix if these bytes complete its first
+    /// occurrence on the current line.
+    fn add_text(&mut self, bytes: &[u8]) {
+        let end = self.buf.get_ref().len();
+        self.add_bytes(bytes);
+        if self.comment_found || bytes.is_empty() {
+            return;
+        }
+        let pos = match self.opts.comment_prefix {
+            None => return,
+            Some(ref prefix) => {
+                // The prefix may have started in an earlier write.
+                let from = cmp::max(self.curcell.start,
+                                    end.saturating_sub(prefix.len() - 1));
+                let haystack = &self.buf.get_ref()[from..];
+                match memchr::memmem::find(haystack, prefix.as_bytes()) {
+                    None => return,
+                    Some(pos) => from + pos,
+                }
+            }
+        };
+        self.comment_found = true;
+
+        let start = self.curcell.start;
+        let mut code_end = pos;
+        while code_end > start && self.buf.get_ref()[code_end - 1] == b' ' {
+            code_end -= 1;
+        }
+        let indent = &self.buf.get_ref()[start..code_end];
+        if self.curline().is_empty() && indent.iter().all(|&b| b == b'\t') {
+            return;
+        }
+        let comment = self.buf.get_ref()[pos..].to_vec();
+        self.buf.get_mut().truncate(code_end);
+        self.buf.set_position(code_end as u64);
+        self.curcell.size = code_end - start;
+        self.term_curcell();
+        self.add_bytes(&comment);
+    }
+
+    /// Removes a trailing `\r` from the current cell, if there is one. This
+    /// is used when a line ends with `\r\n`, where the `\r` may have been
+    /// received in a previous write.
+    fn strip_cr(&mut self) {
+        if self.curcell.size > 0 && self.buf.get_ref().last() == Some(&b'\r') {
+            self.curcell.size -= 1;
+            self.buf.get_mut().pop();
+            let end = self.buf.get_ref().len() as u64;
+            self.buf.set_position(end);
+        }
+    }
+
+    /// Ends the current cell if it has any content. This is used when all
+    /// buffered text is about to be written.
+    fn term_partial_cell(&mut self) {
+        if self.curcell.size > 0 {
+            self.term_curcell();
+        }
+    }
+
+    /// Return the bytes of the given cell.
+    fn cell_bytes(&self, cell: &Cell) -> &[u8] {
+        &self.buf.get_ref()[cell.start..cell.start + cell.size]
+    }
+
+    /// Return the text of every line that isn't blank, as one string per
+    /// cell. A line with tabs isn't blank, even if all of its cells are
+    /// empty. This is used by writers that don't align text with spaces.
+    fn text_rows(&self) -> Vec<Vec<String>> {
+        self.lines.iter()
+            .filter(|line| match line.as_slice() {
+                [] => false,
+                [cell] => cell.size > 0,
+                _ => true,
+            })
+            .map(|line| line.iter().map(|cell| {
+                String::from_utf8_lossy(self.cell_bytes(cell)).into_owned()
+            }).collect())
+            .collect()
+    }
+
+    /// Ends the current cell and removes all buffered text, returning the
+    /// text of every line that isn't blank, and whether the first of those
+    /// lines was written by `write_header`.
+    fn take_rows(&mut self) -> (Vec<Vec<String>>, bool) {
+        self.term_partial_cell();
+        let rows = self.text_rows();
+        let has_header = self.has_header;
+        self.reset();
+        (rows, has_header)
+    }
+
+    /// Ends the current cell, updates the UTF8 width of the cell and starts
+    /// a fresh cell.
+    fn term_curcell(&mut self) {
+        self.flush_check.unflushed = true;
+        let mut curcell = Cell::new(self.buf.position() as usize);
+        mem::swap(&mut self.curcell, &mut curcell);
+
+        curcell.update_width(self.buf.get_ref());
+        if let Some(max_width) = self.opts.max_width_of(self.curline().len()) {
+            if curcell.width > max_width {
+                self.truncate_cell(&mut curcell, max_width);
+                self.curcell.start = self.buf.position() as usize;
+            }
+        }
+        self.curline_mut().push(curcell);
+    }
+
+    /// Truncates the given cell, which must be the last one in the buffer,
+    /// so that it fits in `max_width` columns.
+    fn truncate_cell(&mut self, cell: &mut Cell, max_width: usize) {
+        let (len, use_marker) = {
+            let bytes = &self.buf.get_ref()[cell.start..cell.start + cell.size];
+            truncation(bytes, max_width, &self.opts)
+        };
+        self.buf.get_mut().truncate(cell.start + len);
+        if use_marker {
+            let marker = self.opts.truncation_marker.as_bytes();
+            self.buf.get_mut().extend_from_slice(marker);
+        }
+        let len = self.buf.get_ref().len();
+        self.buf.set_position(len as u64);
+        cell.size = len - cell.start;
+        cell.update_width(self.buf.get_ref());
+    }
+
+    /// Adds the given text to the buffer, splitting it into cells and lines.
+    ///
+    /// `flush` is called whenever a line breaks all previous columns, and
+    /// must write and reset all buffered text.
+    fn write_cells<F>(&mut self, buf: &[u8], mut flush: F) -> io::Result<()>
+        where F: FnMut(&mut TabWriter<W>) -> io::Result<()>
+    {
+        let mut lastterm = 0usize;
+        while let Some(pos) = find_term(&buf[lastterm..], &self.opts.delimiters)
+        {
+            let i = lastterm + pos;
+            self.add_text(&buf[lastterm..i]);
+            if buf[i] == b'\n' {
+                self.strip_cr();
+            }
+            self.term_curcell();
+            lastterm = i + 1;
+            if buf[i] == b'\n' {
+                self.comment_found = false;
+                let mut ends_group =
+                    !self.opts.single_group && self.line_ends_group();
+                if self.opts.ignore_trailing_blank_lines
+                    && !self.opts.single_group
+                {
+                    let blank =
+                        is_blank_line(self.curline(), self.buf.get_ref());
+                    if ends_group && blank {
+                        self.group_end_deferred = true;
+                        ends_group = false;
+                    } else if self.group_end_deferred && !ends_group {
+                        // The blank lines ended the group after all, but
+                        // the current line belongs to the next one.
+                        self.flush_before_curline(&mut flush)?;
+                    }
+                    self.group_end_deferred &= blank;
+                }
+                if ends_group {
+                    if let GroupSeparator::Contains(_) =
+                        self.opts.group_separator
+                    {
+                        self.flush_before_curline(&mut flush)?;
+                    }
+                }
+                self.lines.push(vec!());
+                let full = matches!(self.opts.flush_interval,
+                                    Some(n) if self.lines.len() > n);
+                if ends_group || full {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(ends_group, full, "automatic flush");
+                    flush(self)?;
+                } else if !self.opts.single_group {
+                    if let Some(n) = self.opts.lookahead {
+                        while self.lines.len() > n + 1 {
+                            self.commit_first_line();
+                        }
+                    }
+                }
+            }
+        }
+        self.add_text(&buf[lastterm..]);
+        Ok(())
+    }
+
+    /// Aligns the first buffered line into `committed` using the widths of
+    /// the complete lines, and removes it from the buffer.
+    fn commit_first_line(&mut self) {
+        let (minwidth, minwidths) = (self.opts.minwidth, &self.opts.minwidths);
+        let complete = &self.lines[..self.lines.len() - 1];
+        let blank = blank_lines(complete, self.buf.get_ref(), &self.opts);
+        let is_blank = blank[0];
+        let ws = &mut self.scratch.widths;
+        column_block_widths(ws, complete, minwidth, minwidths, &blank);
+        let mut widths = mem::take(&mut ws[0]);
+        for (w, &committed) in widths.iter_mut().zip(&self.committed_widths) {
+            *w = cmp::max(*w, committed);
+        }
+
+        let line = self.lines.remove(0);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(columns = line.len(), widths = ?widths,
+                        "line committed by lookahead");
+        if let Some(limit) = self.opts.terminal_width {
+            let ws = slice::from_mut(&mut widths);
+            fit_widths(ws, slice::from_ref(&line), &self.opts, limit);
+        }
+        let text = &mut self.scratch.text;
+        text.clear();
+        text.extend_from_slice(self.opts.line_prefix.as_bytes());
+        write_line(text, &line, &widths, self.buf.get_ref(), &self.opts);
+        text.extend_from_slice(self.opts.row_separator.as_bytes());
+        self.committed.extend_from_slice(text);
+        self.stats.add(AlignmentResult {
+            lines: 1,
+            groups: 0,
+            max_columns: line.len(),
+            bytes: self.scratch.text.len(),
+        });
+        // A blank line doesn't break columns, so the widths before it apply
+        // to the lines after it.
+        if !is_blank {
+            self.committed_widths = widths;
+        }
+
+        // The text of the first line is at the start of the buffer.
+        let end = line.last().map_or(0, |cell| cell.start + cell.size);
+        self.buf.get_mut().drain(..end);
+        let pos = self.buf.position();
+        self.buf.set_position(pos - end as u64);
+        for cell in self.lines.iter_mut().flat_map(|line| line.iter_mut()) {
+            cell.start -= end;
+        }
+        self.curcell.start -= end;
+    }
+
+    /// Returns true if the current line, whose cells have all been ended,
+    /// ends the current alignment group.
+    fn line_ends_group(&self) -> bool {
+        let line = self.curline();
+        let buf = self.buf.get_ref();
+        match self.opts.group_separator {
+            // Having a single cell means that *all* previous columns have
+            // been broken, so we should just flush.
+            GroupSeparator::SingleCell => {
+                line.len() == 1
+                    && !(self.opts.preserve_indent && is_blank_line(line, buf))
+            }
+            GroupSeparator::Contains(ref text) => {
+                line.iter().any(|cell| {
+                    contains_bytes(self.cell_bytes(cell), text.as_bytes())
+                })
+            }
+            GroupSeparator::LineCount(n) => self.lines.len() >= n,
+        }
+    }
+
+    /// Flushes all lines before the current one with `flush`, keeping the
+    /// cells of the current line buffered.
+    fn flush_before_curline<F>(&mut self, flush: &mut F) -> io::Result<()>
+        where F: FnMut(&mut TabWriter<W>) -> io::Result<()>
+    {
+        let line = mem::take(self.curline_mut());
+        let cells: Vec<Vec<u8>> =
+            line.iter().map(|cell| self.cell_bytes(cell).to_vec()).collect();
+        flush(self)?;
+        for cell in cells {
+            self.add_bytes(&cell);
+            self.term_curcell();
+        }
+        Ok(())
+    }
+
+    /// Aligns all buffered text into the pending buffer, to be written to an
+    /// asynchronous writer.
+    #[cfg(any(feature = "tokio", feature = "futures-io"))]
+    fn align_pending(&mut self) -> io::Result<()> {
+        self.term_partial_cell();
+        let has_text = !self.committed.is_empty() || self.lines_count() > 0;
+        if self.opts.utf8_bom && !self.bom_written && has_text {
+            self.pending.extend_from_slice(UTF8_BOM);
+            self.bom_written = true;
+            self.stats.bytes += UTF8_BOM.len();
+        }
+        self.pending.append(&mut self.committed);
+        let stats = write_lines(&mut self.pending, &mut self.scratch,
+                                &self.lines, self.buf.get_ref(), &self.opts,
+                                &self.committed_widths)?;
+        self.stats.add(stats);
+        self.flushed_widths = column_maxima(&self.scratch.widths);
+        self.reset();
+        // The aligned text is only written once `pending` has been drained.
+        self.flush_check.unflushed = !self.pending.is_empty();
+        Ok(())
+    }
+
+    /// Returns true if any text has been written to the current line.
+    fn curline_started(&self) -> bool {
+        !self.curline().is_empty() || self.curcell.size > 0
+    }
+
+    /// Return a view of the current line of cells.
+    fn curline(&self) -> &[Cell] {
+        let i = self.lines.len() - 1;
+        &self.lines[i]
+    }
+
+    /// Return a mutable view of the current line of cells.
+    fn curline_mut(&mut self) -> &mut Vec<Cell> {
+        let i = self.lines.len() - 1;
+        &mut self.lines[i]
+    }
+}
+
+impl<W: io::Write> TabWriter<W> {
+    /// Writes a row of cells followed by a newline.
+    ///
+    /// This is equivalent to writing the cells joined by `\t` and followed
+    /// by `\n`, without building the joined string. Cells are separated
+    /// even if a delimiter other than `\t` is set.
+    pub fn write_row<I>(&mut self, cells: I) -> io::Result<()>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        self.write_row_no_newline(cells)?;
+        self.write_all(b"\n")
+    }
+
+    /// Writes a row of cells, like `write_row`, but without a newline.
+    ///
+    /// The last cell is left open, so more text may be written to it before
+    /// ending the line.
+    pub fn write_row_no_newline<I>(&mut self, cells: I) -> io::Result<()>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        for (i, cell) in cells.into_iter().enumerate() {
+            if i > 0 {
+                self.term_curcell();
+            }
+            self.write_all(cell.as_ref().as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes a row of cells, like `write_row`, and marks it as the header
+    /// of the table.
+    ///
+    /// Table writers such as `HtmlTableWriter` set the header apart from the
+    /// other rows, which are its data rows. `TabWriter` itself aligns the
+    /// header like any other row.
+    ///
+    /// # Errors
+    ///
+    /// The header must be the first row, so an error of kind
+    /// `io::ErrorKind::InvalidInput` is returned if any text is buffered,
+    /// which includes a header written before.
+    pub fn write_header<I>(&mut self, cells: I) -> io::Result<()>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        if self.lines_count() > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the header must be the first row",
+            ));
+        }
+        // If the header is written right away, then `reset` clears this.
+        self.has_header = true;
+        self.write_row(cells)
+    }
+
+    /// Writes a line of code followed by a comment, so that the comments of
+    /// consecutive lines are aligned.
+    ///
+    /// This is only sugar for `write_row(&[code, comment])`, and performs
+    /// exactly like writing `code`, `\t`, `comment` and `\n` yourself. The
+    /// comment is written as is, so it should include its comment marker.
+    ///
+    /// ```rust
+    /// use tabwriter::TabWriter;
+    ///
+    /// let mut tw = TabWriter::new_string().padding(1);
+    /// tw.write_comment_aligned("let x = 1;", "// one").unwrap();
+    /// tw.write_comment_aligned("x += 41;", "// the answer").unwrap();
+    /// assert_eq!(tw.into_string().unwrap(), "\
+    /// let x = 1; // one
+    /// x += 41;   // the answer
+    /// ");
+    /// ```
+    pub fn write_comment_aligned(
+        &mut self,
+        code: &str,
+        comment: &str,
+    ) -> io::Result<()> {
+        self.write_row(&[code, comment])
+    }
+
+    /// Writes every row produced by the given iterator with `write_row`.
+    ///
+    /// Each row may be any iterable of cells, e.g., a `Vec<String>`. Rows
+    /// may have different numbers of cells.
+    ///
+    /// ```rust
+    /// use tabwriter::TabWriter;
+    ///
+    /// let table: &[&[&str]] = &[&["a", "b"], &["xx", "yy"]];
+    /// let mut tw = TabWriter::new_string();
+    /// tw.extend_rows(table.iter().cloned()).unwrap();
+    /// assert_eq!(tw.into_string().unwrap(), "a   b\nxx  yy\n");
+    /// ```
+    pub fn extend_rows<I, R>(&mut self, rows: I) -> io::Result<()>
+        where I: IntoIterator<Item=R>,
+              R: IntoIterator,
+              R::Item: AsRef<str>
+    {
+        for row in rows {
+            self.write_row(row)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes this `TabWriter`, like `flush`, and returns statistics about
+    /// all text written to the underlying writer since the last call to
+    /// `flush_with_stats`. This includes the text of every alignment group
+    /// that was written automatically.
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use tabwriter::TabWriter;
+    ///
+    /// let mut tw = TabWriter::new(vec![]);
+    /// write!(&mut tw, "a\tb\nno tabs\nx\ty\tz\n").unwrap();
+    /// let stats = tw.flush_with_stats().unwrap();
+    /// assert_eq!(stats.lines, 3);
+    /// assert_eq!(stats.groups, 2);
+    /// assert_eq!(stats.max_columns, 3);
+    /// assert_eq!(stats.bytes, tw.get_ref().len());
+    /// ```
+    pub fn flush_with_stats(&mut self) -> io::Result<AlignmentResult> {
+        self.flush()?;
+        Ok(mem::take(&mut self.stats))
+    }
+
+    /// Flushes this `TabWriter`, like `flush`, and returns the width of
+    /// every column of the lines that were written by this flush.
+    ///
+    /// Each width is the largest width that the column had in any of those
+    /// lines, not counting its padding. The last cell of a line is never
+    /// padded, so it doesn't count towards the width of its column. This is
+    /// useful to e.g. draw a separator line that matches the written lines.
+    ///
+    /// Lines that were written before, because their alignment group ended
+    /// or because of `lookahead`, are not included.
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use tabwriter::TabWriter;
+    ///
+    /// let mut tw = TabWriter::new(vec![]);
+    /// write!(&mut tw, "a\tbbbb\tc\nxxx\ty\n").unwrap();
+    /// assert_eq!(tw.flush_and_widths().unwrap(), vec![3, 4]);
+    /// ```
+    pub fn flush_and_widths(&mut self) -> io::Result<Vec<usize>> {
+        self.flush()?;
+        Ok(self.flushed_widths.clone())
+    }
+
+    /// Writes all buffered text and resets the buffer, but doesn't flush the
+    /// underlying writer.
+    fn write_all_buffered(&mut self) -> io::Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(lines = self.lines_count(),
+                        bytes = self.buffered_bytes(), "flush");
+        self.term_partial_cell();
+        let result = self.write_buffered();
+        self.check_written(result)?;
+        self.reset();
+        Ok(())
+    }
+
+    /// Writes all buffered text, without resetting the buffer.
+    fn write_buffered(&mut self) -> io::Result<()> {
+        self.write_committed()?;
+        if self.lines_count() > 0 {
+            self.write_bom()?;
+        }
+        let stats = write_lines(&mut self.w, &mut self.scratch, &self.lines,
+                                self.buf.get_ref(), &self.opts,
+                                &self.committed_widths)?;
+        self.stats.add(stats);
+        self.flushed_widths = column_maxima(&self.scratch.widths);
+        Ok(())
+    }
+
+    /// Writes the byte order mark, if it is enabled and hasn't been written
+    /// yet. This must be called before any text is written.
+    fn write_bom(&mut self) -> io::Result<()> {
+        if self.opts.utf8_bom && !self.bom_written {
+            self.w.write_all(UTF8_BOM)?;
+            self.bom_written = true;
+            self.stats.bytes += UTF8_BOM.len();
+        }
+        Ok(())
+    }
+
+    /// Returns `result`. If it is an error, the buffered text is no longer
+    /// checked on drop, since the caller knows that it wasn't written.
+    fn check_written<T>(&mut self, result: io::Result<T>) -> io::Result<T> {
+        if result.is_err() {
+            self.flush_check.unflushed = false;
+        }
+        result
+    }
+
+    /// Writes all lines committed by the lookahead window.
+    fn write_committed(&mut self) -> io::Result<()> {
+        if !self.committed.is_empty() {
+            self.write_bom()?;
+            self.w.write_all(&self.committed)?;
+            self.committed.clear();
+        }
+        Ok(())
+    }
+
+    /// Unwraps this `TabWriter`, returning the underlying writer.
+    ///
+    /// This internal buffer is flushed before returning the writer. If the
+    /// flush fails, then an error is returned.
+    #[allow(clippy::result_large_err)]
+    pub fn into_inner(mut self) -> Result<W, IntoInnerError<TabWriter<W>>> {
+        match self.flush() {
+            Ok(()) => Ok(self.w),
+            Err(err) => Err(IntoInnerError(self, err)),
+        }
+    }
+
+    /// Flushes this `TabWriter` and returns the underlying writer, which is
+    /// replaced with a new default one, so that this `TabWriter` can be used
+    /// to write more text.
+    ///
+    /// Since all buffered text is flushed first, the next text written
+    /// starts a new alignment, exactly as after `flush`. All settings are
+    /// kept. If the flush fails, then the underlying writer is not replaced.
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use tabwriter::TabWriter;
+    ///
+    /// let mut tw = TabWriter::new(vec![]);
+    /// write!(&mut tw, "a\tb\nxx\tyy\n").unwrap();
+    /// assert_eq!(tw.take_inner().unwrap(), b"a   b\nxx  yy\n");
+    /// write!(&mut tw, "c\td\n").unwrap();
+    /// assert_eq!(tw.take_inner().unwrap(), b"c   d\n");
+    /// ```
+    pub fn take_inner(&mut self) -> io::Result<W>
+        where W: Default
+    {
+        self.flush()?;
+        self.bom_written = false;
+        Ok(mem::take(&mut self.w))
+    }
+}
+
+impl<W: io::Write> TabWriter<io::BufWriter<W>> {
+    /// Create a new `TabWriter` that writes to `w` through an
+    /// `io::BufWriter`.
+    ///
+    /// Every aligned line is written to the underlying writer with a few
+    /// small writes, so this is much faster when every write is expensive,
+    /// e.g., a system call. See the `TabWriter` documentation for why the
+    /// `io::BufWriter` goes inside the `TabWriter`.
+    pub fn new_buffered(w: W) -> TabWriter<io::BufWriter<W>> {
+        TabWriter::new(io::BufWriter::new(w))
+    }
+
+    /// Create a new `TabWriter` like `new_buffered`, with an `io::BufWriter`
+    /// that has the given capacity.
+    pub fn new_buffered_with_capacity(
+        w: W,
+        capacity: usize,
+    ) -> TabWriter<io::BufWriter<W>> {
+        TabWriter::new(io::BufWriter::with_capacity(capacity, w))
+    }
+}
+
+impl TabWriter<Vec<u8>> {
+    /// Create a new `TabWriter` that writes to an in-memory buffer.
+    ///
+    /// This is a convenience for `TabWriter::new(Vec::new())`. Use
+    /// `into_string` to retrieve the aligned text.
+    pub fn new_string() -> TabWriter<Vec<u8>> {
+        TabWriter::new(Vec::new())
+    }
+
+    /// Flushes this `TabWriter` and returns the aligned text as a `String`.
+    ///
+    /// An error is returned if flushing fails or if the aligned text is not
+    /// valid UTF-8.
+    pub fn into_string(mut self) -> Result<String, IntoStringError> {
+        self.flush()?;
+        Ok(String::from_utf8(self.w)?)
+    }
+
+    /// Aligns `input` with the default settings, and returns the aligned
+    /// text.
+    ///
+    /// This is the same as the `align` function, for when `TabWriter` is
+    /// already in scope. Use `process` to align text with other settings.
+    ///
+    /// ```rust
+    /// use tabwriter::TabWriter;
+    ///
+    /// assert_eq!(TabWriter::pipe("a\tb\nxx\tyy\n"), "a   b\nxx  yy\n");
+    /// ```
+    pub fn pipe(input: &str) -> String {
+        align(input)
+    }
+
+    /// Writes all of `input`, and returns the aligned text as a `String`.
+    ///
+    /// This is a convenience for `write_all` followed by `into_string`, so
+    /// a configured `TabWriter` can align text in a single expression:
+    ///
+    /// ```rust
+    /// use tabwriter::TabWriter;
+    ///
+    /// let aligned = TabWriter::new_string().padding(1).process("a\tb\nc\td\n");
+    /// assert_eq!(aligned.unwrap(), "a  b\nc  d\n");
+    /// ```
+    pub fn process(mut self, input: &str) -> Result<String, IntoStringError> {
+        self.write_all(input.as_bytes())?;
+        self.into_string()
+    }
+}
+
+impl Default for TabWriter<Vec<u8>> {
+    /// Equivalent to `TabWriter::new(Vec::new())`.
+    fn default() -> TabWriter<Vec<u8>> {
+        TabWriter::new(Vec::new())
+    }
+}
+
+/// Shows all text written so far, aligned as if `flush` had been called.
+///
+/// This `TabWriter` itself is not flushed. Instead, a copy of it is, so
+/// this is as expensive as cloning it. Any invalid UTF-8 is replaced with
+/// `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::TabWriter;
+///
+/// let mut tw = TabWriter::new_string();
+/// write!(&mut tw, "a\tb\nxx\tyy").unwrap();
+/// assert_eq!(tw.to_string(), "a   b\nxx  yy");
+/// // The text is still buffered.
+/// assert_eq!(tw.into_string().unwrap(), "a   b\nxx  yy");
+/// ```
+impl fmt::Display for TabWriter<Vec<u8>> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut tw = self.clone();
+        // Writing to a `Vec<u8>` cannot fail.
+        let _ = tw.flush();
+        f.write_str(&String::from_utf8_lossy(&tw.w))
+    }
+}
+
+/// Flushes this `TabWriter` and iterates over the lines of aligned text,
+/// without their line endings.
+///
+/// Note that the text is split at line endings, so with a
+/// `RowSeparator::Custom` or `RowSeparator::None` row separator, rows are
+/// not split at their separators.
+///
+/// Any invalid UTF-8 is replaced with `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::TabWriter;
+///
+/// let mut tw = TabWriter::new_string();
+/// write!(&mut tw, "a\tb\nxx\tyy\n").unwrap();
+/// let rows: Vec<String> = tw.into_iter().collect();
+/// assert_eq!(rows, vec!["a   b", "xx  yy"]);
+/// ```
+impl IntoIterator for TabWriter<Vec<u8>> {
+    type Item = String;
+    type IntoIter = IntoRows;
+
+    fn into_iter(mut self) -> IntoRows {
+        // Writing to a `Vec<u8>` cannot fail.
+        let _ = self.flush();
+        let text = String::from_utf8_lossy(&self.w);
+        let rows: Vec<String> = text.lines().map(str::to_string).collect();
+        IntoRows(rows.into_iter())
+    }
+}
+
+/// An iterator over the lines of aligned text of a `TabWriter`.
+///
+/// This is created by the `IntoIterator` implementation of
+/// `TabWriter<Vec<u8>>`.
+#[derive(Debug)]
+pub struct IntoRows(::std::vec::IntoIter<String>);
+
+impl Iterator for IntoRows {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Collects text into a `TabWriter` that writes to an in-memory buffer.
+///
+/// Note that the collected text may still be buffered, so `into_string` or
+/// `flush` must be called to get all of the aligned text.
+///
+/// ```rust
+/// use tabwriter::TabWriter;
+///
+/// let tw: TabWriter<Vec<u8>> =
+///     (1..4).map(|i| format!("{}\t{}\n", i, i * 100)).collect();
+/// assert_eq!(tw.into_string().unwrap(), "1   100\n2   200\n3   300\n");
+/// ```
+impl FromIterator<String> for TabWriter<Vec<u8>> {
+    fn from_iter<I: IntoIterator<Item=String>>(iter: I) -> TabWriter<Vec<u8>> {
+        let mut tw = TabWriter::new_string();
+        tw.extend(iter);
+        tw
+    }
+}
+
+/// See the `FromIterator<String>` implementation.
+impl<'a> FromIterator<&'a str> for TabWriter<Vec<u8>> {
+    fn from_iter<I: IntoIterator<Item=&'a str>>(iter: I) -> TabWriter<Vec<u8>> {
+        let mut tw = TabWriter::new_string();
+        tw.extend(iter);
+        tw
+    }
+}
+
+impl<W> From<W> for TabWriter<W> {
+    /// Equivalent to `TabWriter::new(w)`.
+    fn from(w: W) -> TabWriter<W> {
+        TabWriter::new(w)
+    }
+}
+
+impl TabWriterBuilder {
+    /// Create a new builder with the default configuration.
+    pub fn new() -> TabWriterBuilder {
+        TabWriterBuilder::default()
+    }
+
+    /// Build a `TabWriter` for the given writer using this configuration.
+    pub fn build<W>(&self, w: W) -> TabWriter<W> {
+        TabWriter {
+            w,
+            buf: io::Cursor::new(Vec::with_capacity(1024)),
+            lines: vec!(vec!()),
+            curcell: Cell::new(0),
+            opts: self.0.clone(),
+            stats: AlignmentResult::default(),
+            committed: vec![],
+            committed_widths: vec![],
+            group_end_deferred: false,
+            comment_found: false,
+            has_header: false,
+            bom_written: false,
+            flush_check: FlushCheck { enabled: true, unflushed: false },
+            scratch: Scratch::default(),
+            flushed_widths: vec![],
+            #[cfg(any(feature = "tokio", feature = "futures-io"))]
+            pending: vec![],
+        }
+    }
+
+    /// See `TabWriter::minwidth`.
+    pub fn minwidth(mut self, minwidth: usize) -> TabWriterBuilder {
+        self.0.minwidth = minwidth;
+        self
+    }
+
+    /// See `TabWriter::column_minwidth`.
+    pub fn column_minwidth(
+        mut self,
+        col: usize,
+        width: usize,
+    ) -> TabWriterBuilder {
+        set_column(&mut self.0.minwidths, col, Some(width), None);
+        self
+    }
+
+    /// See `TabWriter::padding`.
+    pub fn padding(mut self, padding: usize) -> TabWriterBuilder {
+        self.0.padding = padding;
+        self
+    }
+
+    /// See `TabWriter::column_padding`.
+    pub fn column_padding(
+        mut self,
+        col: usize,
+        padding: usize,
+    ) -> TabWriterBuilder {
+        set_column(&mut self.0.paddings, col, Some(padding), None);
+        self
+    }
+
+    /// See `TabWriter::column_alignment`.
+    pub fn column_alignment(
+        mut self,
+        col: usize,
+        align: Alignment,
+    ) -> TabWriterBuilder {
+        set_column(&mut self.0.alignments, col, align, Alignment::Left);
+        self
+    }
+
+    /// See `TabWriter::max_column_width`.
+    pub fn max_column_width(mut self, max_width: usize) -> TabWriterBuilder {
+        self.0.max_width = Some(max_width);
+        self
+    }
+
+    /// See `TabWriter::truncation_marker`.
+    pub fn truncation_marker(mut self, marker: &str) -> TabWriterBuilder {
+        self.0.truncation_marker = marker.to_string();
+        self
+    }
+
+    /// See `TabWriter::terminal_width`.
+    pub fn terminal_width(mut self, width: usize) -> TabWriterBuilder {
+        self.0.terminal_width = Some(width);
+        self
+    }
+
+    /// See `TabWriter::fill_char`.
+    pub fn fill_char(mut self, fill: char) -> TabWriterBuilder {
+        self.0.fill = check_fill_char(fill);
+        self
+    }
+
+    /// See `TabWriter::column_separator`.
+    pub fn column_separator(mut self, separator: &str) -> TabWriterBuilder {
+        self.0.separator = separator.to_string();
+        self
+    }
+
+    /// See `TabWriter::single_group`.
+    pub fn single_group(mut self, yes: bool) -> TabWriterBuilder {
+        self.0.single_group = yes;
+        self
+    }
+
+    /// See `TabWriter::preserve_indent`.
+    pub fn preserve_indent(mut self, yes: bool) -> TabWriterBuilder {
+        self.0.preserve_indent = yes;
+        self
+    }
+
+    /// See `TabWriter::strip_trailing_whitespace`.
+    pub fn strip_trailing_whitespace(mut self, yes: bool) -> TabWriterBuilder {
+        self.0.strip_trailing = yes;
+        self
+    }
+
+    /// See `TabWriter::group_separator`.
+    pub fn group_separator(mut self, sep: GroupSeparator) -> TabWriterBuilder {
+        self.0.group_separator = check_group_separator(sep);
+        self
+    }
+
+    /// See `TabWriter::flush_every`.
+    pub fn flush_every(mut self, n: usize) -> TabWriterBuilder {
+        self.0.flush_interval = Some(check_flush_interval(n));
+        self
+    }
+
+    /// See `TabWriter::lookahead`.
+    pub fn lookahead(mut self, n: usize) -> TabWriterBuilder {
+        self.0.lookahead = Some(check_lookahead(n));
+        self
+    }
+
+    /// See `TabWriter::normalize_indent`.
+    pub fn normalize_indent(mut self, yes: bool) -> TabWriterBuilder {
+        self.0.normalize_indent = yes;
+        self
+    }
+
+    /// See `TabWriter::ignore_trailing_blank_lines`.
+    pub fn ignore_trailing_blank_lines(
+        mut self,
+        yes: bool,
+    ) -> TabWriterBuilder {
+        self.0.ignore_trailing_blank_lines = yes;
+        self
+    }
+
+    /// See `TabWriter::comment_mode`.
+    pub fn comment_mode(mut self, prefix: &str) -> TabWriterBuilder {
+        self.0.comment_prefix = Some(check_comment_prefix(prefix));
+        self
+    }
+
+    /// See `TabWriter::utf8_bom`.
+    pub fn utf8_bom(mut self, yes: bool) -> TabWriterBuilder {
+        self.0.utf8_bom = yes;
+        self
+    }
+
+    /// See `TabWriter::line_prefix`.
+    pub fn line_prefix(mut self, prefix: &str) -> TabWriterBuilder {
+        self.0.line_prefix = prefix.to_string();
+        self
+    }
+
+    /// See `TabWriter::line_ending`.
+    pub fn line_ending(self, line_ending: LineEnding) -> TabWriterBuilder {
+        self.row_separator(line_ending.into())
+    }
+
+    /// See `TabWriter::row_separator`.
+    pub fn row_separator(mut self, sep: RowSeparator) -> TabWriterBuilder {
+        self.0.row_separator = sep;
+        self
+    }
+
+    /// See `TabWriter::delimiter`.
+    pub fn delimiter(self, delimiter: u8) -> TabWriterBuilder {
+        self.delimiters(&[delimiter])
+    }
+
+    /// See `TabWriter::delimiters`.
+    pub fn delimiters(mut self, delimiters: &[u8]) -> TabWriterBuilder {
+        assert!(!delimiters.is_empty(), "at least one delimiter is required");
+        self.0.delimiters = delimiter_set(delimiters);
+        self
+    }
+
+    /// See `TabWriter::add_column`.
+    pub fn add_column(
+        mut self,
+        col: usize,
+        config: Column,
+    ) -> TabWriterBuilder {
+        self.0.set_column_config(col, config);
+        self
+    }
+}
+
+/// Flushing a `TabWriter` writes all buffered text, and then flushes the
+/// underlying writer. Alignment groups that are written automatically don't
+/// flush the underlying writer.
+impl<W: io::Write> io::Write for TabWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_cells(buf, |tw| tw.write_all_buffered())?;
+        let result = self.write_committed();
+        self.check_written(result)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_all_buffered()?;
+        self.w.flush()
+    }
+}
+
+/// All buffered text is aligned and written before seeking, like
+/// `io::BufWriter` does. So seeking always ends the current alignment group,
+/// even when the position doesn't change.
+impl<W: io::Write + io::Seek> io::Seek for TabWriter<W> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.flush()?;
+        self.w.seek(pos)
+    }
+}
+
+/// Like `TabWriter::get_ref`.
+impl<W> AsRef<W> for TabWriter<W> {
+    fn as_ref(&self) -> &W {
+        &self.w
+    }
+}
+
+/// Like `TabWriter::get_mut`.
+///
+/// # Warning
+///
+/// It is inadvisable to directly write to the underlying writer. Any text
+/// still buffered in this `TabWriter` will be written after it, which will
+/// likely corrupt the alignment.
+impl<W> AsMut<W> for TabWriter<W> {
+    fn as_mut(&mut self) -> &mut W {
+        &mut self.w
+    }
+}
+
+/// Shows the buffered text, the cells that it has been split into so far
+/// and the basic settings. The buffered text is shown as a string if it is
+/// valid UTF-8 and as hex bytes otherwise.
+impl<W: fmt::Debug> fmt::Debug for TabWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let buf = self.buf.get_ref();
+        let mut d = f.debug_struct("TabWriter");
+        d.field("w", &self.w);
+        match str::from_utf8(buf) {
+            Ok(s) => d.field("buf", &s),
+            Err(_) => d.field("buf", &Hex(buf)),
+        };
+        d.field("lines", &self.lines)
+         .field("curcell", &self.curcell)
+         .field("minwidth", &self.opts.minwidth)
+         .field("padding", &self.opts.padding)
+         .finish()
+    }
+}
+
+/// Formats bytes as space separated hex digits.
+struct Hex<'a>(&'a [u8]);
+
+impl<'a> fmt::Debug for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Text written through `fmt::Write` is routed through the same tab and
+/// newline handling as `io::Write`, so the two may be used interchangeably.
+///
+/// If the underlying writer returns an error, then it is converted to a
+/// `fmt::Error`.
+impl<W: io::Write> fmt::Write for TabWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+/// Writes every string through the same tab and newline handling as
+/// `io::Write`.
+///
+/// # Panics
+///
+/// Since `extend` cannot return an error, this panics if the underlying
+/// writer returns an error. Use `write_all` to handle errors instead.
+impl<'a, W: io::Write> Extend<&'a str> for TabWriter<W> {
+    fn extend<I: IntoIterator<Item=&'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.write_all(s.as_bytes()).expect("failed to write text");
+        }
+    }
+}
+
+/// See the `Extend<&str>` implementation.
+impl<W: io::Write> Extend<String> for TabWriter<W> {
+    fn extend<I: IntoIterator<Item=String>>(&mut self, iter: I) {
+        for s in iter {
+            self.extend(Some(&*s));
+        }
+    }
+}
+
+/// Aligns the given text using the default settings of `TabWriter`.
+///
+/// This is a convenience for writing `input` to a `TabWriter` wrapping a
+/// `Vec<u8>`, flushing it and converting the result back to a `String`.
+///
+/// ```rust
+/// assert_eq!(tabwriter::align("a\tb\nxx\tyy\n"), "a   b\nxx  yy\n");
+/// ```
+pub fn align(input: &str) -> String {
+    align_with_opts(input, 2, 2)
+}
+
+/// Aligns the given text using the given minimum width and padding.
+///
+/// See `TabWriter::minwidth` and `TabWriter::padding` for what these
+/// settings mean.
+pub fn align_with_opts(input: &str, minwidth: usize, padding: usize) -> String {
+    let mut tw = TabWriter::new(vec![]).minwidth(minwidth).padding(padding);
+    // Writing to a `Vec<u8>` cannot fail.
+    tw.write_all(input.as_bytes()).unwrap();
+    let bytes = tw.into_inner().unwrap();
+    String::from_utf8(bytes).expect("aligned UTF-8 input must be UTF-8")
+}
+
+/// Returns the width of every column of every line of the given text, as
+/// computed by `TabWriter` with the given minimum width, without writing
+/// anything.
+///
+/// See `cell_widths` for what the widths mean. Note that widths don't
+/// include padding.
+///
+/// ```rust
+/// let widths = tabwriter::compute_widths("a\tb\nxxx\ty\tz\nno tabs\n", 2);
+/// assert_eq!(widths, vec![vec![3], vec![3, 2], vec![]]);
+/// ```
+pub fn compute_widths(input: &str, minwidth: usize) -> Vec<Vec<usize>> {
+    let mut tw = TabWriter::new(());
+    tw.flush_check.enabled = false;
+    // Nothing is ever written, so all lines stay buffered. This is fine,
+    // since a line that ends a group also breaks all contiguous columns.
+    let _ = tw.write_cells(input.as_bytes(), |_| Ok(()));
+    tw.term_partial_cell();
+    if tw.lines.last().map(Vec::len) == Some(0) {
+        tw.lines.pop();
+    }
+    cell_widths(&tw.lines, minwidth)
+}
+
+/// Returns the largest width of every column of the given text, as computed
+/// by `TabWriter` with the given minimum width, without writing anything.
+///
+/// This is the largest width of each column in any line that `compute_widths`
+/// returns, so it takes time linear in the length of `input` as well. Note
+/// that a column may be narrower in lines that don't belong to the same
+/// column block as its widest cell.
+///
+/// ```rust
+/// let widths = tabwriter::column_widths_from_str("a\tb\nxxx\ty\tz\n", 2);
+/// assert_eq!(widths, vec![3, 2]);
+/// ```
+pub fn column_widths_from_str(input: &str, minwidth: usize) -> Vec<usize> {
+    column_maxima(&compute_widths(input, minwidth))
+}
+
+/// Returns the element-wise maximum of the given widths of lines.
+fn column_maxima(lines: &[Vec<usize>]) -> Vec<usize> {
+    let mut maxima = vec![];
+    for widths in lines {
+        if maxima.len() < widths.len() {
+            maxima.resize(widths.len(), 0);
+        }
+        for (max, &width) in maxima.iter_mut().zip(widths) {
+            *max = cmp::max(*max, width);
+        }
+    }
+    maxima
+}
+
+/// An error returned by `into_inner`.
+///
+/// This combines the error that happened while flushing the buffer with the
+/// `TabWriter` itself.
+pub struct IntoInnerError<W>(W, io::Error);
+
+impl<W> IntoInnerError<W> {
+    /// Returns the error which caused the `into_error()` call to fail.
+    pub fn error(&self) -> &io::Error {
+        &self.1
+    }
+
+    /// Returns the `TabWriter` instance which generated the error.
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W> fmt::Debug for IntoInnerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error().fmt(f)
+    }
+}
+
+impl<W> fmt::Display for IntoInnerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error().fmt(f)
+    }
+}
+
+impl<W: ::std::any::Any> error::Error for IntoInnerError<W> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.error())
+    }
+}
+
+/// An error returned by `into_string`.
+///
+/// Writing to a `TabWriter` only ever fails with an `io::Error`, as required
+/// by `io::Write`, and text is aligned whether it is valid UTF-8 or not. So
+/// this error only separates a failure of the underlying writer from aligned
+/// text that isn't valid UTF-8. Both convert into it with `?`:
+///
+/// ```rust
+/// use std::io::Write;
+/// use std::mem;
+/// use tabwriter::{IntoStringError, TabWriter};
+///
+/// fn align_bytes(text: &[u8]) -> Result<String, IntoStringError> {
+///     let mut tw = TabWriter::new(vec![]);
+///     tw.write_all(text)?;
+///     tw.flush()?;
+///     Ok(String::from_utf8(mem::take(tw.get_mut()))?)
+/// }
+/// assert_eq!(align_bytes(b"a\tb\nxx\tyy\n").unwrap(), "a   b\nxx  yy\n");
+/// assert!(align_bytes(b"a\t\xff\n").unwrap_err().utf8_error().is_some());
+/// ```
+#[derive(Debug)]
+pub enum IntoStringError {
+    /// An error that occurred while flushing the buffered text.
+    Io(io::Error),
+    /// The aligned text was not valid UTF-8.
+    Utf8(string::FromUtf8Error),
+}
+
+impl IntoStringError {
+    /// Returns the error that occurred while flushing, if that is what
+    /// caused `into_string` to fail.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match *self {
+            IntoStringError::Io(ref err) => Some(err),
+            IntoStringError::Utf8(_) => None,
+        }
+    }
+
+    /// Returns the UTF-8 decoding error, if that is what caused
+    /// `into_string` to fail.
+    pub fn utf8_error(&self) -> Option<&string::FromUtf8Error> {
+        match *self {
+            IntoStringError::Io(_) => None,
+            IntoStringError::Utf8(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for IntoStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IntoStringError::Io(ref err) => err.fmt(f),
+            IntoStringError::Utf8(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl error::Error for IntoStringError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            IntoStringError::Io(ref err) => Some(err),
+            IntoStringError::Utf8(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for IntoStringError {
+    fn from(err: io::Error) -> IntoStringError {
+        IntoStringError::Io(err)
+    }
+}
+
+impl From<string::FromUtf8Error> for IntoStringError {
+    fn from(err: string::FromUtf8Error) -> IntoStringError {
+        IntoStringError::Utf8(err)
+    }
+}
+
+/// Writes the given lines to `out`, aligned according to `opts`, as a single
+/// alignment group.
+///
+/// `floor` holds the widths of the line written just before these lines in
+/// the same group, if any. Columns that continue from that line are never
+/// made narrower than it.
+fn write_lines<O: io::Write>(
+    out: &mut O,
+    scratch: &mut Scratch,
+    lines: &[Vec<Cell>],
+    buf: &[u8],
+    opts: &Options,
+    floor: &[usize],
+) -> io::Result<AlignmentResult> {
+    let indent = if opts.normalize_indent {
+        common_indent(lines, buf)
+    } else {
+        &[]
+    };
+    let dedented;
+    let (lines, indented): (&[Vec<Cell>], &[bool]) = if indent.is_empty() {
+        (lines, &[])
+    } else {
+        dedented = dedent(lines, buf, indent);
+        (&dedented.0, &dedented.1)
+    };
+    let Scratch { ref mut text, ref mut widths } = *scratch;
+    let (minwidth, minwidths) = (opts.minwidth, &opts.minwidths);
+    if opts.single_group {
+        single_group_widths(widths, lines, minwidth, minwidths);
+    } else {
+        let blank = blank_lines(lines, buf, opts);
+        column_block_widths(widths, lines, minwidth, minwidths, &blank);
+    }
+    for (col, &min) in floor.iter().enumerate() {
+        for ws in widths.iter_mut().take_while(|ws| ws.len() > col) {
+            ws[col] = cmp::max(ws[col], min);
+        }
+    }
+    if let Some(limit) = opts.terminal_width {
+        fit_widths(widths, lines, opts, limit);
+    }
+
+    // The last line is only empty when it has just been started.
+    let nlines = match lines.last() {
+        Some(line) if line.is_empty() => lines.len() - 1,
+        _ => lines.len(),
+    };
+    let mut stats = AlignmentResult {
+        lines: nlines,
+        groups: if nlines > 0 { 1 } else { 0 },
+        max_columns: lines.iter().map(|line| line.len()).max().unwrap_or(0),
+        bytes: 0,
+    };
+
+    // Each line is assembled in `text` first, so that it can be trimmed.
+    let mut first = true;
+    for (i, (line, widths)) in lines.iter().zip(widths.iter()).enumerate() {
+        if !first {
+            out.write_all(opts.row_separator.as_bytes())?;
+            stats.bytes += opts.row_separator.as_bytes().len();
+        } else {
+            first = false
+        }
+        text.clear();
+        // The empty line after the last line ending is not a line of text.
+        if i < nlines {
+            text.extend_from_slice(opts.line_prefix.as_bytes());
+        }
+        if indented.get(i) == Some(&true) {
+            text.extend_from_slice(indent);
+        }
+        write_line(text, line, widths, buf, opts);
+        out.write_all(text)?;
+        stats.bytes += text.len();
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(lines = stats.lines, columns = stats.max_columns,
+                    bytes = stats.bytes, widths = ?column_maxima(widths),
+                    "alignment group written");
+    Ok(stats)
+}
+
+/// Appends a single line, aligned to the given widths, to `text`.
+fn write_line(
+    text: &mut Vec<u8>,
+    line: &[Cell],
+    widths: &[usize],
+    buf: &[u8],
+    opts: &Options,
+) {
+    // When stripping trailing whitespace, the line ends with the last cell
+    // that has any other text, and nothing is written after that text.
+    let end = if opts.strip_trailing {
+        line.iter()
+            .rposition(|cell| {
+                let bytes = &buf[cell.start..cell.start + cell.size];
+                !trim_trailing_whitespace(bytes).is_empty()
+            })
+            .map_or(0, |i| i + 1)
+    } else {
+        line.len()
+    };
+    // The number of columns taken up by the cells written so far.
+    let mut used = 0;
+    for (i, cell) in line[..end].iter().enumerate() {
+        let mut bytes = &buf[cell.start..cell.start + cell.size];
+        if opts.strip_trailing && i + 1 == end {
+            bytes = trim_trailing_whitespace(bytes);
+        }
+        // The last cell is never padded. Note that a blank line may have
+        // widths for columns that it doesn't have.
+        if i + 1 == line.len() {
+            match opts.terminal_width {
+                Some(limit) if used + cell.width > limit => {
+                    let left = limit.saturating_sub(used);
+                    push_truncated(text, bytes, left, opts);
+                }
+                _ => text.extend_from_slice(bytes),
+            }
+            continue;
+        }
+        used += widths[i] + opts.column_gap_of(i);
+        // A cell can only be wider than its column when the column has been
+        // narrowed to fit the terminal width.
+        let truncated;
+        let (bytes, width) = if cell.width > widths[i] {
+            assert!(opts.terminal_width.is_some());
+            let mut cut = vec![];
+            let width = push_truncated(&mut cut, bytes, widths[i], opts);
+            truncated = cut;
+            (&truncated[..], width)
+        } else {
+            (bytes, cell.width)
+        };
+        let extra = widths[i] - width;
+        let (before, after) = opts.alignment(i).split(extra);
+        let fill = opts.fill_of(i);
+        push_fill(text, fill, before);
+        text.extend_from_slice(bytes);
+        if i + 1 == end {
+            continue;
+        }
+        let padsize = opts.column_padding_of(i);
+        push_fill(text, fill, padsize + after);
+        if !opts.separator.is_empty() {
+            text.extend_from_slice(opts.separator.as_bytes());
+            push_fill(text, fill, padsize);
+        }
+    }
+}
+
+/// Returns `bytes` without any spaces or tabs at its end.
+fn trim_trailing_whitespace(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter()
+        .rposition(|&b| b != b' ' && b != b'\t')
+        .map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+/// Narrows the widest columns of the given widths to a common width, such
+/// that no line is wider than `limit` columns, if possible.
+///
+/// The last cell of every line counts as if it were in a column too, so that
+/// it keeps as much of its text as the other cells.
+fn fit_widths(
+    ws: &mut [Vec<usize>],
+    lines: &[Vec<Cell>],
+    opts: &Options,
+    limit: usize,
+) {
+    let line_width = |line: &[Cell], widths: &[usize], cap: usize| {
+        let last = match line.last() {
+            Some(cell) => cmp::min(cell.width, cap),
+            None => return 0,
+        };
+        let cells = (0..line.len() - 1).map(|i| {
+            cmp::min(widths[i], cap) + opts.column_gap_of(i)
+        });
+        last + cells.sum::<usize>()
+    };
+    let fits = |cap: usize| {
+        lines.iter().zip(ws.iter()).all(|(line, widths)| {
+            line_width(line, widths, cap) <= limit
+        })
+    };
+
+    let widest_cell = lines.iter().flat_map(|line| line.iter())
+        .map(|cell| cell.width);
+    let widest = ws.iter().flat_map(|widths| widths.iter()).cloned()
+        .chain(widest_cell).max().unwrap_or(0);
+    if fits(widest) {
+        return;
+    }
+    // Find the widest common width that fits, which is at least 1.
+    let (mut lo, mut hi) = (1, widest);
+    while lo + 1 < hi {
+        let mid = lo + (hi - lo) / 2;
+        if fits(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    for w in ws.iter_mut().flat_map(|widths| widths.iter_mut()) {
+        *w = cmp::min(*w, lo);
+    }
+}
+
+/// Returns the length of the prefix of `bytes` that fits in `max_width`
+/// columns together with the truncation marker, and whether the marker fits
+/// at all.
+fn truncation(bytes: &[u8], max_width: usize, opts: &Options) -> (usize, bool) {
+    let marker_width = display_columns(opts.truncation_marker.as_bytes());
+    let use_marker = marker_width <= max_width;
+    let limit = if use_marker { max_width - marker_width } else { max_width };
+    (truncated_len(bytes, limit), use_marker)
+}
+
+/// Appends `bytes` to `text`, truncated with the truncation marker so that
+/// it fits in `max_width` columns, and returns the width of what was
+/// appended.
+fn push_truncated(
+    text: &mut Vec<u8>,
+    bytes: &[u8],
+    max_width: usize,
+    opts: &Options,
+) -> usize {
+    let start = text.len();
+    let (len, use_marker) = truncation(bytes, max_width, opts);
+    text.extend_from_slice(&bytes[..len]);
+    if use_marker {
+        text.extend_from_slice(opts.truncation_marker.as_bytes());
+    }
+    display_columns(&text[start..])
+}
+
+/// Appends `n` copies of the given fill character to `text`.
+fn push_fill(text: &mut Vec<u8>, fill: char, n: usize) {
+    let mut encoded = [0; 4];
+    let encoded = fill.encode_utf8(&mut encoded).as_bytes();
+    for _ in 0..n {
+        text.extend_from_slice(encoded);
+    }
+}
+
+/// Returns the given delimiter bytes, sorted and without duplicates.
+fn delimiter_set(delimiters: &[u8]) -> Vec<u8> {
+    let mut set = delimiters.to_vec();
+    for &b in &set {
+        assert!(b.is_ascii(), "delimiter must be an ASCII byte, got {:?}", b);
+        assert!(b != b'\n', "delimiter must not be a newline");
+    }
+    set.sort_unstable();
+    set.dedup();
+    set
+}
+
+/// Returns the position of the first newline or delimiter in `haystack`.
+///
+/// The common cases of one or two delimiters are handed to `memchr`, which
+/// is much faster than looking at every byte.
+fn find_term(haystack: &[u8], delimiters: &[u8]) -> Option<usize> {
+    match *delimiters {
+        [a] => memchr::memchr2(a, b'\n', haystack),
+        [a, b] => memchr::memchr3(a, b, b'\n', haystack),
+        _ => haystack.iter()
+                     .position(|&c| c == b'\n' || delimiters.contains(&c)),
+    }
+}
+
+/// Panics if the given fill character does not occupy exactly one column.
+fn check_fill_char(fill: char) -> char {
+    assert!(char_columns(fill) == 1,
+            "fill character must be exactly one column wide, got {:?}", fill);
+    fill
+}
+
+/// Panics if the given comment prefix is empty or contains a newline.
+fn check_comment_prefix(prefix: &str) -> String {
+    assert!(!prefix.is_empty(), "comment prefix must not be empty");
+    assert!(!prefix.contains('\n'),
+            "comment prefix must not contain a newline");
+    prefix.to_string()
+}
+
+/// Panics if the given group separator would match every line.
+fn check_group_separator(sep: GroupSeparator) -> GroupSeparator {
+    if let GroupSeparator::Contains(ref text) = sep {
+        assert!(!text.is_empty(), "group separator text must not be empty");
+    }
+    sep
+}
+
+/// Panics if the given flush interval is zero.
+fn check_flush_interval(n: usize) -> usize {
+    assert!(n > 0, "flush interval must be at least one line");
+    n
+}
+
+/// Panics if the given lookahead window is zero.
+fn check_lookahead(n: usize) -> usize {
+    assert!(n > 0, "lookahead must be at least one line");
+    n
+}
+
+/// Sets the value for the column at index `col`, growing `values` with
+/// `fill` as needed.
+fn set_column<T: Clone>(values: &mut Vec<T>, col: usize, value: T, fill: T) {
+    if values.len() <= col {
+        values.resize(col + 1, fill);
+    }
+    values[col] = value;
+}
+
+/// Returns true if the given line has a single cell that contains nothing but
+/// whitespace.
+fn is_blank_line(line: &[Cell], buf: &[u8]) -> bool {
+    line.len() == 1 && buf[line[0].start..line[0].start + line[0].size]
+        .iter()
+        .all(|b| b.is_ascii_whitespace())
+}
+
+/// The UTF-8 encoding of `U+FEFF BYTE ORDER MARK`.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Panics when dropped in a debug build if `unflushed` is set, which means
+/// that text written to a `TabWriter` was never written to its underlying
+/// writer. This is a field rather than a `Drop` implementation of
+/// `TabWriter` itself, so that the underlying writer can still be moved out
+/// of it.
+#[derive(Debug)]
+struct FlushCheck {
+    enabled: bool,
+    unflushed: bool,
+}
+
+impl Clone for FlushCheck {
+    /// The text of a clone is a copy, so dropping it unflushed loses nothing.
+    fn clone(&self) -> FlushCheck {
+        FlushCheck { enabled: self.enabled, unflushed: false }
+    }
+}
+
+impl Drop for FlushCheck {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions)
+            && self.enabled
+            && self.unflushed
+            && !thread::panicking()
+        {
+            panic!("TabWriter dropped with unflushed data; call flush() \
+                    before dropping");
+        }
+    }
+}
+
+/// Buffers that are reused by every call to `write_lines`.
+#[derive(Clone, Debug, Default)]
+struct Scratch {
+    /// Each aligned line is assembled here before it is written.
+    text: Vec<u8>,
+    /// The widths of the columns of every line.
+    widths: Vec<Vec<usize>>,
+}
+
+/// Returns the leading spaces and tabs that every line has in common,
+/// ignoring lines that contain nothing but whitespace.
+fn common_indent<'a>(lines: &[Vec<Cell>], buf: &'a [u8]) -> &'a [u8] {
+    let mut common: Option<&[u8]> = None;
+    for line in lines {
+        if line.is_empty() || is_blank_line(line, buf) {
+            continue;
+        }
+        let first = &buf[line[0].start..line[0].start + line[0].size];
+        let n = first.iter().take_while(|&&b| b == b' ' || b == b'\t').count();
+        common = Some(match common {
+            None => &first[..n],
+            Some(prev) => {
+                let len = prev.iter().zip(&first[..n])
+                              .take_while(|&(a, b)| a == b)
+                              .count();
+                &prev[..len]
+            }
+        });
+    }
+    common.unwrap_or(&[])
+}
+
+/// Removes `indent` from the start of every line that begins with it.
+/// Returns the new lines and whether each line was dedented.
+fn dedent(
+    lines: &[Vec<Cell>],
+    buf: &[u8],
+    indent: &[u8],
+) -> (Vec<Vec<Cell>>, Vec<bool>) {
+    let mut dedented = lines.to_vec();
+    let mut indented = vec![false; lines.len()];
+    for (line, indented) in dedented.iter_mut().zip(&mut indented) {
+        if let Some(cell) = line.first_mut() {
+            let bytes = &buf[cell.start..cell.start + cell.size];
+            if bytes.starts_with(indent) {
+                cell.start += indent.len();
+                cell.size -= indent.len();
+                cell.update_width(buf);
+                *indented = true;
+            }
+        }
+    }
+    (dedented, indented)
+}
+
+/// Returns which of the given lines are skipped over when computing widths.
+fn blank_lines(lines: &[Vec<Cell>], buf: &[u8], opts: &Options) -> Vec<bool> {
+    lines.iter()
+         .map(|line| opts.preserve_indent && is_blank_line(line, buf))
+         .collect()
+}
+
+/// Returns true if `needle` occurs anywhere in `haystack`. The `needle`
+/// must not be empty.
+fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+/// Like `column_block_widths`, except every column is aligned across all lines,
+/// regardless of whether it is contiguous.
+fn single_group_widths(
+    ws: &mut Vec<Vec<usize>>,
+    lines: &[Vec<Cell>],
+    minwidth: usize,
+    minwidths: &[Option<usize>],
+) {
+    let mut colws: Vec<usize> = vec![];
+    for line in lines {
+        // As in `column_block_widths`, the last cell of each line is ignored.
+        let ncols = line.len().saturating_sub(1);
+        for (col, cell) in line[..ncols].iter().enumerate() {
+            if col >= colws.len() {
+                colws.push(minwidths.get(col).cloned().flatten()
+                                    .unwrap_or(minwidth));
+            }
+            colws[col] = cmp::max(colws[col], cell.width);
+        }
+    }
+    clear_widths(ws, lines.len());
+    for (w, line) in ws.iter_mut().zip(lines) {
+        w.extend_from_slice(&colws[..line.len().saturating_sub(1)]);
+    }
+}
+
+/// Returns the length in bytes of the longest prefix of `bytes` that fits in
+/// `limit` display columns without splitting a character.
+fn truncated_len(bytes: &[u8], limit: usize) -> usize {
+    match str::from_utf8(bytes) {
+        Err(_) => cmp::min(bytes.len(), limit),
+        Ok(s) => {
+            let mut width = 0;
+            for (i, c) in s.char_indices() {
+                width += char_columns(c);
+                if width > limit {
+                    return i;
+                }
+            }
+            s.len()
+        }
+    }
+}
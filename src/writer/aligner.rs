@@ -0,0 +1,158 @@
+use std::io;
+
+use super::{blank_lines, column_block_widths, TabWriter};
+
+/// TabAligner aligns tabbed text like `TabWriter`, but instead of writing
+/// the aligned text, it keeps every aligned cell for custom rendering, e.g.,
+/// with colors or borders.
+///
+/// Text is written to a `TabAligner` with `write` and `flush`, just like it
+/// is written to a `TabWriter`, and `rows` then returns every line that has
+/// been flushed, as the cells of that line. Every cell knows its width and
+/// how many columns of padding follow it, so that it lines up with the
+/// cells of the same column in other lines. Like with `TabWriter`, the last
+/// cell of a line is never padded.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::TabAligner;
+///
+/// let mut ta = TabAligner::new();
+/// write!(&mut ta, "name\tcount\napples\t3\n").unwrap();
+/// ta.flush().unwrap();
+///
+/// let mut rendered = String::new();
+/// for row in ta.rows() {
+///     for cell in row {
+///         rendered.push_str(&format!("<{}>", cell.content));
+///         rendered.push_str(&" ".repeat(cell.padding));
+///     }
+///     rendered.push('\n');
+/// }
+/// assert_eq!(rendered, "<name>    <count>\n<apples>  <3>\n");
+/// ```
+#[derive(Debug)]
+pub struct TabAligner {
+    tw: TabWriter<()>,
+    rows: Vec<Vec<Aligned>>,
+}
+
+/// A cell that has been aligned, as stored by a `TabAligner`.
+#[derive(Debug)]
+struct Aligned {
+    content: String,
+    width: usize,
+    padded_width: usize,
+}
+
+/// A single aligned cell, as returned by `TabAligner::rows`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlignedCell<'a> {
+    /// The text of the cell, without the tab that ended it. Bytes that are
+    /// not valid UTF-8 are replaced with `U+FFFD`.
+    pub content: &'a str,
+    /// The display width of `content`.
+    pub width: usize,
+    /// The number of columns that the cell and its padding take up together.
+    /// This is the same for all cells of a column.
+    pub padded_width: usize,
+    /// The number of columns of padding after `content`, i.e.,
+    /// `padded_width - width`.
+    pub padding: usize,
+}
+
+impl TabAligner {
+    /// Create a new `TabAligner` with the default settings of `TabWriter`.
+    ///
+    /// Note that `flush` must be called before text is available from
+    /// `rows`.
+    pub fn new() -> TabAligner {
+        let mut tw = TabWriter::new(());
+        // Text that is never flushed is simply missing from `rows`.
+        tw.flush_check.enabled = false;
+        TabAligner { tw, rows: vec![] }
+    }
+
+    /// Set the minimum width of each column. See `TabWriter::minwidth`.
+    pub fn minwidth(mut self, minwidth: usize) -> TabAligner {
+        self.tw = self.tw.minwidth(minwidth);
+        self
+    }
+
+    /// Set the padding between columns. See `TabWriter::padding`.
+    pub fn padding(mut self, padding: usize) -> TabAligner {
+        self.tw = self.tw.padding(padding);
+        self
+    }
+
+    /// Returns every line that has been flushed, as an iterator over its
+    /// aligned cells.
+    ///
+    /// A line without any tabs has a single cell, which is empty for an
+    /// empty line.
+    pub fn rows(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = AlignedCell<'_>>> {
+        self.rows.iter().map(|row| {
+            row.iter().map(|cell| AlignedCell {
+                content: &cell.content,
+                width: cell.width,
+                padded_width: cell.padded_width,
+                padding: cell.padded_width - cell.width,
+            })
+        })
+    }
+
+    /// Removes all lines that have been flushed, so that `rows` only returns
+    /// lines that are flushed after this.
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+}
+
+impl Default for TabAligner {
+    fn default() -> TabAligner {
+        TabAligner::new()
+    }
+}
+
+impl io::Write for TabAligner {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // All lines stay buffered until `flush`. This is fine, since a line
+        // that ends a group also breaks all contiguous columns.
+        self.tw.write_cells(buf, |_| Ok(()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.tw.term_partial_cell();
+        // The last line is only empty when it has just been started.
+        if self.tw.lines.last().map(Vec::len) == Some(0) {
+            self.tw.lines.pop();
+        }
+
+        let tw = &self.tw;
+        let (lines, buf, opts) = (&tw.lines, tw.buf.get_ref(), &tw.opts);
+        let blank = blank_lines(lines, buf, opts);
+        let mut widths = vec![];
+        column_block_widths(
+            &mut widths, lines, opts.minwidth, &opts.minwidths, &blank);
+        for (line, widths) in lines.iter().zip(&widths) {
+            let row = line.iter().enumerate().map(|(col, cell)| {
+                let padded_width = match widths.get(col) {
+                    Some(&w) => w + opts.column_padding_of(col),
+                    None => cell.width,
+                };
+                Aligned {
+                    content: String::from_utf8_lossy(tw.cell_bytes(cell))
+                        .into_owned(),
+                    width: cell.width,
+                    padded_width,
+                }
+            }).collect();
+            self.rows.push(row);
+        }
+        self.tw.reset();
+        Ok(())
+    }
+}
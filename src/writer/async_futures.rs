@@ -0,0 +1,14 @@
+use futures_io::AsyncWrite;
+
+impl_async_write! {
+    /// Buffered text is aligned exactly as it is by the `io::Write`
+    /// implementation, and the aligned text is written to the underlying
+    /// writer as it becomes available.
+    ///
+    /// Like `io::Write::flush`, `poll_flush` must be called to guarantee
+    /// that all text is written. It also flushes the underlying writer.
+    ///
+    /// This is the same as the implementation of Tokio's `AsyncWrite`, for
+    /// writers from the `futures` ecosystem, such as `async-std`.
+    AsyncWrite, poll_close
+}
@@ -0,0 +1,11 @@
+use tokio::io::AsyncWrite;
+
+impl_async_write! {
+    /// Buffered text is aligned exactly as it is by the `io::Write`
+    /// implementation, and the aligned text is written to the underlying
+    /// writer as it becomes available.
+    ///
+    /// Like `io::Write::flush`, `poll_flush` must be called to guarantee
+    /// that all text is written. It also flushes the underlying writer.
+    AsyncWrite, poll_shutdown
+}
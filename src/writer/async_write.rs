@@ -0,0 +1,79 @@
+/// Returns `Poll::Pending` if the given poll is pending, and otherwise
+/// evaluates to its result, returning early on errors.
+///
+/// This is `std::task::ready!` followed by `?`, which is too new for our
+/// minimum Rust version.
+macro_rules! try_ready {
+    ($poll:expr) => {
+        match $poll {
+            ::std::task::Poll::Ready(result) => result?,
+            ::std::task::Poll::Pending => return ::std::task::Poll::Pending,
+        }
+    };
+}
+
+/// Implements the given `AsyncWrite` trait for `TabWriter`, where `$close`
+/// is the name of the trait's method that shuts down the writer.
+///
+/// Tokio and `futures-io` each have their own `AsyncWrite` trait, which only
+/// differ in the name of that method.
+macro_rules! impl_async_write {
+    ($(#[$attr:meta])* $trait:ident, $close:ident) => {
+        $(#[$attr])*
+        impl<W: $trait + Unpin> $trait for $crate::TabWriter<W> {
+            fn poll_write(
+                mut self: ::std::pin::Pin<&mut Self>,
+                cx: &mut ::std::task::Context,
+                buf: &[u8],
+            ) -> ::std::task::Poll<::std::io::Result<usize>> {
+                let this = &mut *self;
+                try_ready!(poll_write_pending(
+                    &mut this.w, &mut this.pending, cx));
+                this.write_cells(buf, |tw| tw.align_pending())?;
+                this.pending.append(&mut this.committed);
+                ::std::task::Poll::Ready(Ok(buf.len()))
+            }
+
+            fn poll_flush(
+                mut self: ::std::pin::Pin<&mut Self>,
+                cx: &mut ::std::task::Context,
+            ) -> ::std::task::Poll<::std::io::Result<()>> {
+                let this = &mut *self;
+                this.align_pending()?;
+                try_ready!(poll_write_pending(
+                    &mut this.w, &mut this.pending, cx));
+                this.flush_check.unflushed = false;
+                ::std::pin::Pin::new(&mut this.w).poll_flush(cx)
+            }
+
+            fn $close(
+                mut self: ::std::pin::Pin<&mut Self>,
+                cx: &mut ::std::task::Context,
+            ) -> ::std::task::Poll<::std::io::Result<()>> {
+                try_ready!(self.as_mut().poll_flush(cx));
+                ::std::pin::Pin::new(&mut self.w).$close(cx)
+            }
+        }
+
+        /// Writes all of `pending` to `w`, removing whatever was written.
+        fn poll_write_pending<W: $trait + Unpin>(
+            w: &mut W,
+            pending: &mut Vec<u8>,
+            cx: &mut ::std::task::Context,
+        ) -> ::std::task::Poll<::std::io::Result<()>> {
+            while !pending.is_empty() {
+                let n = try_ready!(
+                    ::std::pin::Pin::new(&mut *w).poll_write(cx, pending));
+                if n == 0 {
+                    return ::std::task::Poll::Ready(Err(
+                        ::std::io::Error::new(
+                            ::std::io::ErrorKind::WriteZero,
+                            "failed to write aligned text",
+                        )));
+                }
+                pending.drain(..n);
+            }
+            ::std::task::Poll::Ready(Ok(()))
+        }
+    };
+}
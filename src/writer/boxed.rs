@@ -0,0 +1,236 @@
+use std::io;
+
+use super::{Alignment, TabWriter};
+use super::table::{pad_cell, pad_last_cell, table_widths};
+
+/// The characters used to draw the borders of a `BoxTableWriter`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoxStyle {
+    /// Unicode box-drawing characters, e.g., `┌─┬─┐`. This is the default.
+    #[default]
+    Unicode,
+    /// Plain ASCII characters, e.g., `+-+-+`.
+    Ascii,
+    /// No borders at all. Columns are separated by spaces instead, like
+    /// with `TabWriter`.
+    None,
+}
+
+/// The characters of one horizontal border: left end, line, junction and
+/// right end.
+type Rule = (char, char, char, char);
+
+impl BoxStyle {
+    fn top(&self) -> Option<Rule> {
+        match *self {
+            BoxStyle::Unicode => Some(('┌', '─', '┬', '┐')),
+            BoxStyle::Ascii => Some(('+', '-', '+', '+')),
+            BoxStyle::None => None,
+        }
+    }
+
+    fn header(&self) -> Option<Rule> {
+        match *self {
+            BoxStyle::Unicode => Some(('╞', '═', '╪', '╡')),
+            BoxStyle::Ascii => Some(('+', '=', '+', '+')),
+            BoxStyle::None => None,
+        }
+    }
+
+    fn bottom(&self) -> Option<Rule> {
+        match *self {
+            BoxStyle::Unicode => Some(('└', '─', '┴', '┘')),
+            BoxStyle::Ascii => Some(('+', '-', '+', '+')),
+            BoxStyle::None => None,
+        }
+    }
+
+    fn vertical(&self) -> Option<char> {
+        match *self {
+            BoxStyle::Unicode => Some('│'),
+            BoxStyle::Ascii => Some('|'),
+            BoxStyle::None => None,
+        }
+    }
+}
+
+/// BoxTableWriter wraps an arbitrary writer and writes tabbed text as a
+/// table with borders drawn around every cell.
+///
+/// Every non-empty line of text becomes a row of the table, and every tab
+/// delimited field becomes a cell. Rows with fewer cells than others are
+/// filled up with empty cells.
+///
+/// Unlike `TabWriter`, all rows written between flushes form a single
+/// table, so nothing is written until `flush` is called.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::BoxTableWriter;
+///
+/// let mut bw = BoxTableWriter::new(vec![]).header_separator(true);
+/// write!(&mut bw, "name\tcount\napples\t3\n").unwrap();
+/// bw.flush().unwrap();
+///
+/// let written = String::from_utf8(bw.into_inner().unwrap()).unwrap();
+/// assert_eq!(written, "\
+/// ┌────────┬───────┐
+/// │ name   │ count │
+/// ╞════════╪═══════╡
+/// │ apples │ 3     │
+/// └────────┴───────┘
+/// ");
+/// ```
+#[derive(Debug)]
+pub struct BoxTableWriter<W> {
+    tw: TabWriter<W>,
+    box_style: BoxStyle,
+    header_separator: bool,
+}
+
+impl<W: io::Write> BoxTableWriter<W> {
+    /// Create a new `BoxTableWriter` from an existing `Writer`.
+    ///
+    /// Note that `flush` must be called to write the table to the given
+    /// writer.
+    pub fn new(w: W) -> BoxTableWriter<W> {
+        BoxTableWriter {
+            tw: TabWriter::new(w).single_group(true),
+            box_style: BoxStyle::Unicode,
+            header_separator: false,
+        }
+    }
+
+    /// Set the characters used to draw borders, or `BoxStyle::None` to
+    /// draw no borders.
+    ///
+    /// The default style is `BoxStyle::Unicode`.
+    pub fn box_style(mut self, style: BoxStyle) -> BoxTableWriter<W> {
+        self.box_style = style;
+        self
+    }
+
+    /// When enabled, a double line is drawn after the first row of every
+    /// table, to set it apart as a header.
+    ///
+    /// This is disabled by default.
+    pub fn header_separator(mut self, yes: bool) -> BoxTableWriter<W> {
+        self.header_separator = yes;
+        self
+    }
+
+    /// Set the alignment of the column at index `col`, where the first
+    /// column has index `0`.
+    ///
+    /// Columns without an explicit alignment are left aligned.
+    pub fn column_alignment(
+        mut self,
+        col: usize,
+        align: Alignment,
+    ) -> BoxTableWriter<W> {
+        self.tw = self.tw.column_alignment(col, align);
+        self
+    }
+
+    /// Writes a row of cells and marks it as the header of the table, like
+    /// `TabWriter::write_header`.
+    ///
+    /// The header is followed by a double line, as with `header_separator`.
+    pub fn write_header<I>(&mut self, cells: I) -> io::Result<()>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        self.tw.write_header(cells)
+    }
+
+    /// Writes a single row, where missing cells are left empty if there are
+    /// borders.
+    fn write_row(
+        &mut self,
+        row: &[String],
+        widths: &[usize],
+    ) -> io::Result<()> {
+        let mut text = String::new();
+        match self.box_style.vertical() {
+            Some(vertical) => {
+                text.push(vertical);
+                for (i, &width) in widths.iter().enumerate() {
+                    let align = self.tw.opts.alignment(i);
+                    let cell = pad_cell(row, i, width, align);
+                    text.push_str(&format!(" {} {}", cell, vertical));
+                }
+            }
+            None => {
+                let padding = " ".repeat(self.tw.opts.padding);
+                // Like with `TabWriter`, any missing cells are left out,
+                // and there is no padding after the last cell.
+                for (i, &width) in widths.iter().enumerate().take(row.len()) {
+                    let align = self.tw.opts.alignment(i);
+                    if i + 1 == row.len() {
+                        text.push_str(&pad_last_cell(row, i, width, align));
+                    } else {
+                        text.push_str(&pad_cell(row, i, width, align));
+                        text.push_str(&padding);
+                    }
+                }
+            }
+        }
+        self.tw.w.write_all(text.as_bytes())?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes a horizontal border across all columns, if there is one.
+    fn write_rule(
+        &mut self,
+        rule: Option<Rule>,
+        widths: &[usize],
+    ) -> io::Result<()> {
+        let (left, line, junction, right) = match rule {
+            Some(rule) => rule,
+            None => return Ok(()),
+        };
+        let mut text = left.to_string();
+        for (i, &width) in widths.iter().enumerate() {
+            if i > 0 {
+                text.push(junction);
+            }
+            text.push_str(&line.to_string().repeat(width + 2));
+        }
+        text.push(right);
+        self.tw.w.write_all(text.as_bytes())?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes all buffered rows as a single table, unless there are none.
+    fn write_table(&mut self) -> io::Result<()> {
+        let (rows, has_header) = self.tw.take_rows();
+        let header = self.header_separator || has_header;
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let widths = table_widths(&rows, 0);
+
+        let style = self.box_style;
+        self.write_rule(style.top(), &widths)?;
+        for (i, row) in rows.iter().enumerate() {
+            self.write_row(row, &widths)?;
+            if i == 0 && header && rows.len() > 1 {
+                self.write_rule(style.header(), &widths)?;
+            }
+        }
+        self.write_rule(style.bottom(), &widths)
+    }
+}
+
+table_writer_methods!(BoxTableWriter);
+
+impl<W: io::Write> io::Write for BoxTableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_table()?;
+        self.tw.w.flush()
+    }
+}
@@ -0,0 +1,112 @@
+use std::io;
+
+use super::{LineEnding, TabWriter};
+
+/// CsvTableWriter wraps an arbitrary writer and writes tabbed text as
+/// RFC 4180 CSV.
+///
+/// Every non-empty line of text becomes a record, and every tab delimited
+/// field becomes a field of that record. Records with fewer fields than
+/// others are filled up with empty fields. A field is quoted if it contains
+/// a comma, a double quote, a carriage return or a line feed, and double
+/// quotes inside it are doubled. Note that a line feed always ends a line
+/// of tabbed text, so of the two, only a carriage return can be part of a
+/// field that was written as text.
+///
+/// Since the same tabbed text can be written to a `TabWriter` and to a
+/// `CsvTableWriter`, this makes it easy to produce both aligned text for
+/// people and CSV for programs.
+///
+/// Like the other table writers, all records written between flushes form
+/// a single table, so nothing is written until `flush` is called.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::CsvTableWriter;
+///
+/// let mut cw = CsvTableWriter::new(vec![]);
+/// write!(&mut cw, "name\tcount\n\"big\" apples, red\t3\n").unwrap();
+/// cw.flush().unwrap();
+///
+/// let written = String::from_utf8(cw.into_inner().unwrap()).unwrap();
+/// assert_eq!(written, "name,count\r\n\"\"\"big\"\" apples, red\",3\r\n");
+/// ```
+#[derive(Debug)]
+pub struct CsvTableWriter<W> {
+    tw: TabWriter<W>,
+}
+
+impl<W: io::Write> CsvTableWriter<W> {
+    /// Create a new `CsvTableWriter` from an existing `Writer`.
+    ///
+    /// Note that `flush` must be called to write the records to the given
+    /// writer.
+    pub fn new(w: W) -> CsvTableWriter<W> {
+        let tw = TabWriter::new(w)
+            .single_group(true)
+            .line_ending(LineEnding::CrLf);
+        CsvTableWriter { tw }
+    }
+
+    /// Set the line ending written after every record.
+    ///
+    /// The default line ending is `LineEnding::CrLf`, as required by
+    /// RFC 4180.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> CsvTableWriter<W> {
+        self.tw = self.tw.line_ending(line_ending);
+        self
+    }
+
+    /// When enabled, the UTF-8 byte order mark is written before the first
+    /// record, which e.g. Microsoft Excel needs to read the file as UTF-8.
+    /// See `TabWriter::utf8_bom`.
+    ///
+    /// This is disabled by default.
+    pub fn utf8_bom(mut self, yes: bool) -> CsvTableWriter<W> {
+        self.tw = self.tw.utf8_bom(yes);
+        self
+    }
+
+    /// Writes all buffered rows as a single table, unless there are none.
+    fn write_table(&mut self) -> io::Result<()> {
+        let (rows, _) = self.tw.take_rows();
+
+        if !rows.is_empty() {
+            self.tw.write_bom()?;
+        }
+        let nfields = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in &rows {
+            for i in 0..nfields {
+                if i > 0 {
+                    self.tw.w.write_all(b",")?;
+                }
+                let field = row.get(i).map(|f| &**f).unwrap_or("");
+                self.tw.w.write_all(quote(field).as_bytes())?;
+            }
+            self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+table_writer_methods!(CsvTableWriter);
+
+impl<W: io::Write> io::Write for CsvTableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_table()?;
+        self.tw.w.flush()
+    }
+}
+
+/// Quotes a field if it contains any character that is special to CSV.
+pub(crate) fn quote(field: &str) -> String {
+    if field.contains(&[',', '"', '\r', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
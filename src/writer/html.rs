@@ -0,0 +1,147 @@
+use std::io;
+
+use super::{set_column, TabWriter};
+
+/// HtmlTableWriter wraps an arbitrary writer and writes tabbed text as an
+/// HTML table.
+///
+/// Every non-empty line of text becomes a `<tr>` row, and every tab
+/// delimited field becomes a `<td>` cell. Cell content is escaped, so any
+/// text may be written.
+///
+/// Unlike `TabWriter`, all rows written between flushes form a single
+/// table, so nothing is written until `flush` is called.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::HtmlTableWriter;
+///
+/// let mut hw = HtmlTableWriter::new(vec![])
+///     .header_row(true)
+///     .column_class(1, "num");
+/// write!(&mut hw, "name\tcount\nfish & chips\t3\n").unwrap();
+/// hw.flush().unwrap();
+///
+/// let written = String::from_utf8(hw.into_inner().unwrap()).unwrap();
+/// assert_eq!(written, "\
+/// <table>
+/// <tr><th>name</th><th class=\"num\">count</th></tr>
+/// <tr><td>fish &amp; chips</td><td class=\"num\">3</td></tr>
+/// </table>
+/// ");
+/// ```
+#[derive(Debug)]
+pub struct HtmlTableWriter<W> {
+    tw: TabWriter<W>,
+    header_row: bool,
+    classes: Vec<Option<String>>,
+}
+
+impl<W: io::Write> HtmlTableWriter<W> {
+    /// Create a new `HtmlTableWriter` from an existing `Writer`.
+    ///
+    /// Note that `flush` must be called to write the table to the given
+    /// writer.
+    pub fn new(w: W) -> HtmlTableWriter<W> {
+        HtmlTableWriter {
+            tw: TabWriter::new(w).single_group(true),
+            header_row: false,
+            classes: vec![],
+        }
+    }
+
+    /// When enabled, the cells of the first row of every table are written
+    /// as `<th>` header cells.
+    ///
+    /// This is disabled by default.
+    pub fn header_row(mut self, yes: bool) -> HtmlTableWriter<W> {
+        self.header_row = yes;
+        self
+    }
+
+    /// Set the `class` attribute of every cell in the column at index `col`,
+    /// where the first column has index `0`.
+    ///
+    /// By default, cells have no `class` attribute.
+    pub fn column_class(
+        mut self,
+        col: usize,
+        class: &str,
+    ) -> HtmlTableWriter<W> {
+        set_column(&mut self.classes, col, Some(escape(class)), None);
+        self
+    }
+
+    /// Writes a row of cells and marks it as the header of the table, like
+    /// `TabWriter::write_header`.
+    ///
+    /// The cells of the header are written as `<th>` header cells, as with
+    /// `header_row`.
+    pub fn write_header<I>(&mut self, cells: I) -> io::Result<()>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        self.tw.write_header(cells)
+    }
+
+    /// Writes a single row, using `tag` for every cell.
+    fn write_row(&mut self, row: &[String], tag: &str) -> io::Result<()> {
+        self.tw.w.write_all(b"<tr>")?;
+        for (i, cell) in row.iter().enumerate() {
+            match self.classes.get(i).and_then(|c| c.as_ref()) {
+                Some(class) => {
+                    write!(self.tw.w, "<{} class=\"{}\">", tag, class)?
+                }
+                None => write!(self.tw.w, "<{}>", tag)?,
+            }
+            write!(self.tw.w, "{}</{}>", escape(cell), tag)?;
+        }
+        self.tw.w.write_all(b"</tr>")?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes all buffered rows as a single table, unless there are none.
+    fn write_table(&mut self) -> io::Result<()> {
+        let (rows, has_header) = self.tw.take_rows();
+        let header = self.header_row || has_header;
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        self.tw.w.write_all(b"<table>")?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())?;
+        for (i, row) in rows.iter().enumerate() {
+            let tag = if i == 0 && header { "th" } else { "td" };
+            self.write_row(row, tag)?;
+        }
+        self.tw.w.write_all(b"</table>")?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+}
+
+table_writer_methods!(HtmlTableWriter);
+
+impl<W: io::Write> io::Write for HtmlTableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_table()?;
+        self.tw.w.flush()
+    }
+}
+
+/// Escapes the characters in `text` that have a special meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,138 @@
+use std::io;
+
+use super::{Alignment, TabWriter};
+use super::table::{pad_cell, table_widths};
+
+/// LatexTabularWriter wraps an arbitrary writer and writes tabbed text as a
+/// LaTeX `tabular` environment.
+///
+/// Every non-empty line of text becomes a row of the table, and every tab
+/// delimited field becomes a cell. Cells are separated by `&` and every row
+/// ends with `\\`. Characters that are special to LaTeX are escaped, so any
+/// text may be written. Cells are also padded, so that the table is readable
+/// in the LaTeX source.
+///
+/// Unlike `TabWriter`, all rows written between flushes form a single
+/// table, so nothing is written until `flush` is called.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::{Alignment, LatexTabularWriter};
+///
+/// let mut lw = LatexTabularWriter::new(vec![])
+///     .column_alignment(1, Alignment::Right);
+/// write!(&mut lw, "name\tcount\nfish & chips\t3\n").unwrap();
+/// lw.flush().unwrap();
+///
+/// let written = String::from_utf8(lw.into_inner().unwrap()).unwrap();
+/// assert_eq!(written, "\
+/// \\begin{tabular}{lr}
+/// name          & count \\\\
+/// fish \\& chips &     3 \\\\
+/// \\end{tabular}
+/// ");
+/// ```
+#[derive(Debug)]
+pub struct LatexTabularWriter<W> {
+    tw: TabWriter<W>,
+}
+
+impl<W: io::Write> LatexTabularWriter<W> {
+    /// Create a new `LatexTabularWriter` from an existing `Writer`.
+    ///
+    /// Note that `flush` must be called to write the table to the given
+    /// writer.
+    pub fn new(w: W) -> LatexTabularWriter<W> {
+        LatexTabularWriter { tw: TabWriter::new(w).single_group(true) }
+    }
+
+    /// Set the alignment of the column at index `col`, where the first
+    /// column has index `0`.
+    ///
+    /// The alignment is used both for the column specification of the
+    /// table (`l`, `r` or `c`) and for padding the cells.
+    ///
+    /// Columns without an explicit alignment are left aligned.
+    pub fn column_alignment(
+        mut self,
+        col: usize,
+        align: Alignment,
+    ) -> LatexTabularWriter<W> {
+        self.tw = self.tw.column_alignment(col, align);
+        self
+    }
+
+    /// Writes a single row, where missing cells are left empty.
+    fn write_row(
+        &mut self,
+        row: &[String],
+        widths: &[usize],
+    ) -> io::Result<()> {
+        for (i, &width) in widths.iter().enumerate() {
+            let cell = pad_cell(row, i, width, self.tw.opts.alignment(i));
+            let sep = if i == 0 { "" } else { " & " };
+            write!(self.tw.w, "{}{}", sep, cell)?;
+        }
+        self.tw.w.write_all(b" \\\\")?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes all buffered rows as a single table, unless there are none.
+    fn write_table(&mut self) -> io::Result<()> {
+        let (rows, _) = self.tw.take_rows();
+        let rows: Vec<Vec<String>> = rows.iter()
+            .map(|row| row.iter().map(|cell| escape(cell)).collect())
+            .collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let widths = table_widths(&rows, 0);
+
+        let spec: String = (0..widths.len()).map(|i| {
+            match self.tw.opts.alignment(i) {
+                Alignment::Left => 'l',
+                Alignment::Right => 'r',
+                Alignment::Center => 'c',
+            }
+        }).collect();
+        write!(self.tw.w, "\\begin{{tabular}}{{{}}}", spec)?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())?;
+        for row in &rows {
+            self.write_row(row, &widths)?;
+        }
+        self.tw.w.write_all(b"\\end{tabular}")?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+}
+
+table_writer_methods!(LatexTabularWriter);
+
+impl<W: io::Write> io::Write for LatexTabularWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_table()?;
+        self.tw.w.flush()
+    }
+}
+
+/// Escapes the characters that are special to LaTeX.
+fn escape(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+    for c in cell.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,162 @@
+use std::io;
+
+use super::{Alignment, TabWriter};
+use super::table::{pad_cell, table_widths};
+
+/// MarkdownTableWriter wraps an arbitrary writer and writes tabbed text as
+/// a GitHub Flavored Markdown table.
+///
+/// Every non-empty line of text becomes a row of the table, and every tab
+/// delimited field becomes a cell. All columns, including the last one, are
+/// padded so that the table is also readable as plain text. The first row
+/// is used as the header of the table, unless one is set with `header`.
+///
+/// Unlike `TabWriter`, all rows written between flushes form a single
+/// table, so nothing is written until `flush` is called.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::{Alignment, MarkdownTableWriter};
+///
+/// let mut mw = MarkdownTableWriter::new(vec![])
+///     .header(&["name", "count"])
+///     .column_alignment(1, Alignment::Right);
+/// write!(&mut mw, "apples\t3\nbananas\t12\n").unwrap();
+/// mw.flush().unwrap();
+///
+/// let written = String::from_utf8(mw.into_inner().unwrap()).unwrap();
+/// assert_eq!(written, "\
+/// | name    | count |
+/// | ------- | ----: |
+/// | apples  |     3 |
+/// | bananas |    12 |
+/// ");
+/// ```
+#[derive(Debug)]
+pub struct MarkdownTableWriter<W> {
+    tw: TabWriter<W>,
+    header: Option<Vec<String>>,
+}
+
+impl<W: io::Write> MarkdownTableWriter<W> {
+    /// Create a new `MarkdownTableWriter` from an existing `Writer`.
+    ///
+    /// Note that `flush` must be called to write the table to the given
+    /// writer.
+    pub fn new(w: W) -> MarkdownTableWriter<W> {
+        MarkdownTableWriter {
+            tw: TabWriter::new(w).single_group(true),
+            header: None,
+        }
+    }
+
+    /// Set the header of the table. When a header is set, every line of text
+    /// written becomes a row in the body of the table.
+    ///
+    /// By default, the first line of text is used as the header.
+    pub fn header(mut self, header: &[&str]) -> MarkdownTableWriter<W> {
+        self.header = Some(header.iter().map(|&h| escape(h)).collect());
+        self
+    }
+
+    /// Set the alignment of the column at index `col`, where the first
+    /// column has index `0`.
+    ///
+    /// The alignment is used both for the delimiter row of the table (`---`,
+    /// `---:` or `:---:`) and for padding the cells.
+    ///
+    /// Columns without an explicit alignment are left aligned.
+    pub fn column_alignment(
+        mut self,
+        col: usize,
+        align: Alignment,
+    ) -> MarkdownTableWriter<W> {
+        self.tw = self.tw.column_alignment(col, align);
+        self
+    }
+
+    /// Writes a row of cells and marks it as the header of the table, like
+    /// `TabWriter::write_header`.
+    ///
+    /// The written header replaces any header set with `header`.
+    pub fn write_header<I>(&mut self, cells: I) -> io::Result<()>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        self.tw.write_header(cells)
+    }
+
+    /// Writes a single row, where missing cells are left empty.
+    fn write_row(
+        &mut self,
+        row: &[String],
+        widths: &[usize],
+    ) -> io::Result<()> {
+        self.tw.w.write_all(b"|")?;
+        for (i, &width) in widths.iter().enumerate() {
+            let cell = pad_cell(row, i, width, self.tw.opts.alignment(i));
+            write!(self.tw.w, " {} |", cell)?;
+        }
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes the delimiter row that separates the header from the body.
+    fn write_delimiter_row(&mut self, widths: &[usize]) -> io::Result<()> {
+        self.tw.w.write_all(b"|")?;
+        for (i, &width) in widths.iter().enumerate() {
+            let delim = match self.tw.opts.alignment(i) {
+                Alignment::Left => "-".repeat(width),
+                Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+                Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+            };
+            write!(self.tw.w, " {} |", delim)?;
+        }
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes all buffered rows as a single table, unless there are none.
+    fn write_table(&mut self) -> io::Result<()> {
+        let (text_rows, has_header) = self.tw.take_rows();
+        // A table is only written when there is new text, even if there is a
+        // header.
+        if text_rows.is_empty() {
+            return Ok(());
+        }
+
+        // A written header replaces the one set with `header`.
+        let mut rows = vec![];
+        if !has_header {
+            rows.extend(self.header.clone());
+        }
+        for row in &text_rows {
+            rows.push(row.iter().map(|cell| escape(cell)).collect());
+        }
+        // Every delimiter needs at least three dashes.
+        let widths = table_widths(&rows, 3);
+
+        self.write_row(&rows[0], &widths)?;
+        self.write_delimiter_row(&widths)?;
+        for row in &rows[1..] {
+            self.write_row(row, &widths)?;
+        }
+        Ok(())
+    }
+}
+
+table_writer_methods!(MarkdownTableWriter);
+
+impl<W: io::Write> io::Write for MarkdownTableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_table()?;
+        self.tw.w.flush()
+    }
+}
+
+/// Escapes the pipe characters in a cell, so that they are not mistaken for
+/// cell boundaries.
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
@@ -0,0 +1,123 @@
+use std::cmp;
+use std::io::{self, Write};
+use std::mem;
+
+use super::TabWriter;
+
+/// TabReader wraps an arbitrary reader of tabbed text, and reads that text
+/// back aligned.
+///
+/// This is the pull based counterpart of `TabWriter`: text read from the
+/// underlying reader is aligned by a `TabWriter`, and every alignment group
+/// becomes available to `read` as soon as it is complete. The last group
+/// is available once the underlying reader reaches the end of its input.
+///
+/// ```rust
+/// use std::io::Read;
+/// use tabwriter::TabReader;
+///
+/// let mut tr = TabReader::new("a\tb\nxx\tyy\n".as_bytes());
+/// let mut aligned = String::new();
+/// tr.read_to_string(&mut aligned).unwrap();
+/// assert_eq!(aligned, "a   b\nxx  yy\n");
+/// ```
+#[derive(Debug)]
+pub struct TabReader<R> {
+    r: R,
+    tw: TabWriter<Vec<u8>>,
+    /// Aligned text that has not been read yet, starting at `pos`.
+    out: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: io::Read> TabReader<R> {
+    /// Create a new `TabReader` from an existing `Reader`, which aligns text
+    /// with the default settings of `TabWriter`.
+    pub fn new(r: R) -> TabReader<R> {
+        TabReader::with_writer(r, TabWriter::new(vec![]))
+    }
+
+    /// Create a new `TabReader` that aligns text with the given `TabWriter`,
+    /// so that all of its settings apply.
+    ///
+    /// ```rust
+    /// use std::io::Read;
+    /// use tabwriter::{TabReader, TabWriter};
+    ///
+    /// let tw = TabWriter::new(vec![]).padding(1);
+    /// let mut tr = TabReader::with_writer("a\tb\nxx\tyy\n".as_bytes(), tw);
+    /// let mut aligned = String::new();
+    /// tr.read_to_string(&mut aligned).unwrap();
+    /// assert_eq!(aligned, "a  b\nxx yy\n");
+    /// ```
+    pub fn with_writer(r: R, mut tw: TabWriter<Vec<u8>>) -> TabReader<R> {
+        // Text that is never read is lost anyway, as `into_inner` says.
+        tw.flush_check.enabled = false;
+        TabReader { r, tw, out: vec![], pos: 0, eof: false }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.r
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// # Warning
+    ///
+    /// It is inadvisable to directly read from the underlying reader, since
+    /// the text read would be missing from the aligned text.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.r
+    }
+
+    /// Unwraps this `TabReader`, returning the underlying reader.
+    ///
+    /// Any text that has been read from the underlying reader, but not yet
+    /// been read as aligned text, is lost.
+    pub fn into_inner(self) -> R {
+        self.r
+    }
+
+    /// Reads the next chunk of text from the underlying reader, and makes
+    /// whatever aligned text it completes available.
+    fn fill(&mut self) -> io::Result<()> {
+        let mut chunk = [0; 8 * 1024];
+        let n = match self.r.read(&mut chunk) {
+            Ok(n) => n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        if n == 0 {
+            self.eof = true;
+            self.tw.flush()?;
+        } else {
+            self.tw.write_all(&chunk[..n])?;
+        }
+        self.out.clear();
+        self.pos = 0;
+        mem::swap(&mut self.out, self.tw.get_mut());
+        Ok(())
+    }
+}
+
+impl<R: io::Read> io::Read for TabReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.pos == self.out.len() {
+            if self.eof {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let n = cmp::min(buf.len(), self.out.len() - self.pos);
+        buf[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
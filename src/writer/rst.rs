@@ -0,0 +1,131 @@
+use std::io;
+
+use super::{Alignment, TabWriter};
+use super::table::{pad_cell, table_widths};
+
+/// RstTableWriter wraps an arbitrary writer and writes tabbed text as a
+/// reStructuredText grid table, e.g., for Sphinx documentation.
+///
+/// Every non-empty line of text becomes a row of the table, and every tab
+/// delimited field becomes a cell. Rows with fewer cells than others are
+/// filled up with empty cells. Every column is as wide as its widest cell,
+/// plus one space on each side, so no cell ever overflows its borders.
+///
+/// Unlike `TabWriter`, all rows written between flushes form a single
+/// table, so nothing is written until `flush` is called.
+///
+/// ```rust
+/// use std::io::Write;
+/// use tabwriter::RstTableWriter;
+///
+/// let mut rw = RstTableWriter::new(vec![]).header_row(true);
+/// write!(&mut rw, "name\tcount\napples\t3\n").unwrap();
+/// rw.flush().unwrap();
+///
+/// let written = String::from_utf8(rw.into_inner().unwrap()).unwrap();
+/// assert_eq!(written, "\
+/// +--------+-------+
+/// | name   | count |
+/// +========+=======+
+/// | apples | 3     |
+/// +--------+-------+
+/// ");
+/// ```
+#[derive(Debug)]
+pub struct RstTableWriter<W> {
+    tw: TabWriter<W>,
+    header_row: bool,
+}
+
+impl<W: io::Write> RstTableWriter<W> {
+    /// Create a new `RstTableWriter` from an existing `Writer`.
+    ///
+    /// Note that `flush` must be called to write the table to the given
+    /// writer.
+    pub fn new(w: W) -> RstTableWriter<W> {
+        RstTableWriter {
+            tw: TabWriter::new(w).single_group(true),
+            header_row: false,
+        }
+    }
+
+    /// When enabled, the first row of every table is separated from the
+    /// others by a line of `=`, which makes it the header of the table.
+    ///
+    /// This is disabled by default.
+    pub fn header_row(mut self, yes: bool) -> RstTableWriter<W> {
+        self.header_row = yes;
+        self
+    }
+
+    /// Writes a row of cells and marks it as the header of the table, like
+    /// `TabWriter::write_header`.
+    ///
+    /// The header is followed by a line of `=`, as with `header_row`.
+    pub fn write_header<I>(&mut self, cells: I) -> io::Result<()>
+        where I: IntoIterator, I::Item: AsRef<str>
+    {
+        self.tw.write_header(cells)
+    }
+
+    /// Writes a single row, where missing cells are left empty.
+    fn write_row(
+        &mut self,
+        row: &[String],
+        widths: &[usize],
+    ) -> io::Result<()> {
+        self.tw.w.write_all(b"|")?;
+        for (i, &width) in widths.iter().enumerate() {
+            let cell = pad_cell(row, i, width, Alignment::Left);
+            write!(self.tw.w, " {} |", cell)?;
+        }
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes a border between rows, drawn with the given character.
+    fn write_rule(&mut self, line: char, widths: &[usize]) -> io::Result<()> {
+        let mut text = "+".to_string();
+        for &width in widths {
+            text.push_str(&line.to_string().repeat(width + 2));
+            text.push('+');
+        }
+        self.tw.w.write_all(text.as_bytes())?;
+        self.tw.w.write_all(self.tw.opts.row_separator.as_bytes())
+    }
+
+    /// Writes all buffered rows as a single table, unless there are none.
+    fn write_table(&mut self) -> io::Result<()> {
+        let (rows, has_header) = self.tw.take_rows();
+        let header = self.header_row || has_header;
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let widths = table_widths(&rows, 0);
+
+        self.write_rule('-', &widths)?;
+        for (i, row) in rows.iter().enumerate() {
+            self.write_row(row, &widths)?;
+            let line = if i == 0 && header && rows.len() > 1 {
+                '='
+            } else {
+                '-'
+            };
+            self.write_rule(line, &widths)?;
+        }
+        Ok(())
+    }
+}
+
+table_writer_methods!(RstTableWriter);
+
+impl<W: io::Write> io::Write for RstTableWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_table()?;
+        self.tw.w.flush()
+    }
+}
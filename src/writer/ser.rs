@@ -0,0 +1,786 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use super::TabWriter;
+
+/// TableSerializer writes a sequence of values as the rows of a table to a
+/// `TabWriter`, so that every field becomes a cell.
+///
+/// Each element of the serialized sequence is one row. A struct, map, tuple
+/// or sequence becomes a row with a cell for each of its fields or values,
+/// and any other value becomes a row with a single cell. Fields must be
+/// simple values, such as numbers, strings, booleans, unit variants or
+/// options of these. `None` and `()` are written as empty cells.
+///
+/// Tabs, newlines and carriage returns in values are written as `\t`, `\n`
+/// and `\r`, and any other delimiter of the `TabWriter` is written as `\x`
+/// followed by its two hex digits, e.g., `\x2c` for a comma, so that they
+/// don't break the table. Backslashes are written as `\\`, so that the
+/// original values can be recovered.
+///
+/// Like any other text, the rows are only written to the underlying writer
+/// of the `TabWriter` once they are aligned, so `flush` must still be
+/// called on it.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use std::io::Write;
+/// use tabwriter::{TabWriter, TableSerializer};
+///
+/// let mut fruit = BTreeMap::new();
+/// fruit.insert("apples", 3);
+/// fruit.insert("bananas", 12);
+///
+/// let mut tw = TabWriter::new(vec![]);
+/// TableSerializer::new(&mut tw).serialize(&fruit).unwrap();
+/// tw.flush().unwrap();
+///
+/// let written = String::from_utf8(tw.into_inner().unwrap()).unwrap();
+/// assert_eq!(written, "apples   3\nbananas  12\n");
+/// ```
+#[derive(Debug)]
+pub struct TableSerializer<'a, W: 'a> {
+    tw: &'a mut TabWriter<W>,
+    header: bool,
+    wrote_header: bool,
+}
+
+impl<'a, W: io::Write> TableSerializer<'a, W> {
+    /// Create a new `TableSerializer` that writes rows to the given
+    /// `TabWriter`.
+    pub fn new(tw: &'a mut TabWriter<W>) -> TableSerializer<'a, W> {
+        TableSerializer { tw, header: false, wrote_header: false }
+    }
+
+    /// When enabled, the field names of the first row are written as a
+    /// header row before it. This only applies to rows that are structs or
+    /// maps, which have field names.
+    ///
+    /// This is disabled by default.
+    pub fn header(mut self, yes: bool) -> TableSerializer<'a, W> {
+        self.header = yes;
+        self
+    }
+
+    /// Serializes the given value as rows of the table.
+    ///
+    /// The value must be a sequence, such as a `Vec`, or a map, whose
+    /// entries are then written as rows of a key and a value.
+    pub fn serialize<T>(&mut self, value: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    /// Writes a single row, preceded by the header if it is the first.
+    fn write_row(&mut self, row: Row) -> Result<(), SerializeError> {
+        let delimiters = self.tw.opts.delimiters.clone();
+        let escaped = |cells: &[String]| -> Vec<String> {
+            cells.iter().map(|cell| escape(cell, &delimiters)).collect()
+        };
+        if self.header && !self.wrote_header && !row.names.is_empty() {
+            self.tw.write_row(escaped(&row.names))?;
+        }
+        self.wrote_header = true;
+        self.tw.write_row(escaped(&row.cells))?;
+        Ok(())
+    }
+}
+
+/// An error that occurred while serializing a table.
+#[derive(Debug)]
+pub enum SerializeError {
+    /// The underlying writer returned an error.
+    Io(io::Error),
+    /// The value can't be written as a table, or its `Serialize`
+    /// implementation reported an error.
+    Message(String),
+}
+
+impl From<io::Error> for SerializeError {
+    fn from(err: io::Error) -> SerializeError {
+        SerializeError::Io(err)
+    }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SerializeError::Io(ref err) => err.fmt(f),
+            SerializeError::Message(ref msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl error::Error for SerializeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            SerializeError::Io(ref err) => Some(err),
+            SerializeError::Message(_) => None,
+        }
+    }
+}
+
+impl ser::Error for SerializeError {
+    fn custom<T: fmt::Display>(msg: T) -> SerializeError {
+        SerializeError::Message(msg.to_string())
+    }
+}
+
+/// Returns the error for a value that can't be written where it is.
+fn unsupported(what: &str) -> SerializeError {
+    SerializeError::Message(format!("{} can't be written to a table", what))
+}
+
+/// The cells of one row, and the names of its fields if it has any.
+#[derive(Debug, Default)]
+struct Row {
+    names: Vec<String>,
+    cells: Vec<String>,
+}
+
+/// Forwards the serialization of simple values to `CellSerializer`, and
+/// passes the result of that to `$finish`.
+macro_rules! forward_cells {
+    ($finish:ident; $($name:ident($ty:ty),)*) => {
+        $(
+            fn $name(self, v: $ty) -> Result<Self::Ok, SerializeError> {
+                let cell = CellSerializer.$name(v)?;
+                self.$finish(cell)
+            }
+        )*
+    };
+}
+
+macro_rules! forward_all_cells {
+    ($finish:ident) => {
+        forward_cells! {
+            $finish;
+            serialize_bool(bool),
+            serialize_i8(i8),
+            serialize_i16(i16),
+            serialize_i32(i32),
+            serialize_i64(i64),
+            serialize_i128(i128),
+            serialize_u8(u8),
+            serialize_u16(u16),
+            serialize_u32(u32),
+            serialize_u64(u64),
+            serialize_u128(u128),
+            serialize_f32(f32),
+            serialize_f64(f64),
+            serialize_char(char),
+            serialize_str(&str),
+            serialize_bytes(&[u8]),
+        }
+
+        fn serialize_none(self) -> Result<Self::Ok, SerializeError> {
+            self.$finish(String::new())
+        }
+
+        fn serialize_unit(self) -> Result<Self::Ok, SerializeError> {
+            self.$finish(String::new())
+        }
+
+        fn serialize_unit_struct(
+            self,
+            _: &'static str,
+        ) -> Result<Self::Ok, SerializeError> {
+            self.$finish(String::new())
+        }
+
+        fn serialize_unit_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            variant: &'static str,
+        ) -> Result<Self::Ok, SerializeError> {
+            self.$finish(variant.to_string())
+        }
+    };
+}
+
+impl<'a, 'b, W: io::Write> ser::Serializer for &'b mut TableSerializer<'a, W> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = TableMap<'a, 'b, W>;
+    type SerializeStruct = Impossible<(), SerializeError>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    forward_all_cells!(not_a_table);
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        Err(unsupported("an enum variant with fields"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, SerializeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, SerializeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, SerializeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(unsupported("an enum variant with fields"))
+    }
+
+    fn serialize_map(
+        self,
+        _: Option<usize>,
+    ) -> Result<Self::SerializeMap, SerializeError> {
+        Ok(TableMap { table: self, key: None })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, SerializeError> {
+        Err(unsupported("a single struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(unsupported("an enum variant with fields"))
+    }
+}
+
+impl<'a, W: io::Write> TableSerializer<'a, W> {
+    /// Fails for a simple value where a sequence of rows is expected.
+    fn not_a_table(&self, _: String) -> Result<(), SerializeError> {
+        Err(SerializeError::Message(
+            "only a sequence or a map can be written as a table".to_string()))
+    }
+}
+
+impl<'a, 'b, W: io::Write> ser::SerializeSeq
+    for &'b mut TableSerializer<'a, W>
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        let row = value.serialize(RowSerializer)?;
+        self.write_row(row)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: io::Write> ser::SerializeTuple
+    for &'b mut TableSerializer<'a, W>
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'b, W: io::Write> ser::SerializeTupleStruct
+    for &'b mut TableSerializer<'a, W>
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+/// Writes every entry of a map as a row with the key as its first cell.
+///
+/// This is what `TableSerializer` uses to serialize a map, and it can't be
+/// created in any other way.
+#[derive(Debug)]
+pub struct TableMap<'a: 'b, 'b, W: 'a> {
+    table: &'b mut TableSerializer<'a, W>,
+    key: Option<String>,
+}
+
+impl<'a, 'b, W: io::Write> ser::SerializeMap for TableMap<'a, 'b, W> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        self.key = Some(key.serialize(CellSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        let mut row = value.serialize(RowSerializer)?;
+        row.cells.insert(0, self.key.take().unwrap_or_default());
+        if !row.names.is_empty() {
+            row.names.insert(0, String::new());
+        }
+        self.table.write_row(row)
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+/// Serializes a single value as a row of cells.
+struct RowSerializer;
+
+impl RowSerializer {
+    /// Returns a row with a single cell.
+    fn one_cell(self, cell: String) -> Result<Row, SerializeError> {
+        Ok(Row { names: vec![], cells: vec![cell] })
+    }
+}
+
+impl ser::Serializer for RowSerializer {
+    type Ok = Row;
+    type Error = SerializeError;
+    type SerializeSeq = Row;
+    type SerializeTuple = Row;
+    type SerializeTupleStruct = Row;
+    type SerializeTupleVariant = Row;
+    type SerializeMap = RowMap;
+    type SerializeStruct = Row;
+    type SerializeStructVariant = Row;
+
+    forward_all_cells!(one_cell);
+
+    fn serialize_some<T>(self, value: &T) -> Result<Row, SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Row, SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Row, SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Row, SerializeError> {
+        Ok(Row::default())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Row, SerializeError> {
+        Ok(Row::default())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Row, SerializeError> {
+        Ok(Row::default())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Row, SerializeError> {
+        Ok(Row::default())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<RowMap, SerializeError> {
+        Ok(RowMap::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Row, SerializeError> {
+        Ok(Row::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Row, SerializeError> {
+        Ok(Row::default())
+    }
+}
+
+impl Row {
+    /// Adds a cell for the given value.
+    fn push<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        self.cells.push(value.serialize(CellSerializer)?);
+        Ok(())
+    }
+
+    /// Adds a cell for the given value, along with the name of its field.
+    fn push_named<T: ?Sized + Serialize>(
+        &mut self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        self.names.push(name.to_string());
+        self.push(value)
+    }
+}
+
+/// Implements the traits for serializing the fields of a sequence or tuple
+/// like row.
+macro_rules! row_of_values {
+    ($($trait_:ident::$method:ident),*) => {
+        $(
+            impl ser::$trait_ for Row {
+                type Ok = Row;
+                type Error = SerializeError;
+
+                fn $method<T>(
+                    &mut self,
+                    value: &T,
+                ) -> Result<(), SerializeError>
+                    where T: ?Sized + Serialize
+                {
+                    self.push(value)
+                }
+
+                fn end(self) -> Result<Row, SerializeError> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+}
+
+row_of_values!(SerializeSeq::serialize_element,
+               SerializeTuple::serialize_element,
+               SerializeTupleStruct::serialize_field,
+               SerializeTupleVariant::serialize_field);
+
+/// Implements the traits for serializing the fields of a struct like row.
+macro_rules! row_of_fields {
+    ($($trait_:ident),*) => {
+        $(
+            impl ser::$trait_ for Row {
+                type Ok = Row;
+                type Error = SerializeError;
+
+                fn serialize_field<T>(
+                    &mut self,
+                    name: &'static str,
+                    value: &T,
+                ) -> Result<(), SerializeError>
+                    where T: ?Sized + Serialize
+                {
+                    self.push_named(name, value)
+                }
+
+                fn end(self) -> Result<Row, SerializeError> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+}
+
+row_of_fields!(SerializeStruct, SerializeStructVariant);
+
+/// Builds a row from a map, where the keys are the names of the fields.
+#[derive(Default)]
+struct RowMap {
+    row: Row,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for RowMap {
+    type Ok = Row;
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        self.key = Some(key.serialize(CellSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), SerializeError>
+        where T: ?Sized + Serialize
+    {
+        self.row.names.push(self.key.take().unwrap_or_default());
+        self.row.push(value)
+    }
+
+    fn end(self) -> Result<Row, SerializeError> {
+        Ok(self.row)
+    }
+}
+
+/// Serializes a simple value as the text of a single cell.
+struct CellSerializer;
+
+impl CellSerializer {
+    fn display<T: fmt::Display>(v: T) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+}
+
+/// Writes simple values with their `Display` implementation.
+macro_rules! display_cells {
+    ($($name:ident($ty:ty),)*) => {
+        $(
+            fn $name(self, v: $ty) -> Result<String, SerializeError> {
+                CellSerializer::display(v)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for CellSerializer {
+    type Ok = String;
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<String, SerializeError>;
+    type SerializeTuple = Impossible<String, SerializeError>;
+    type SerializeTupleStruct = Impossible<String, SerializeError>;
+    type SerializeTupleVariant = Impossible<String, SerializeError>;
+    type SerializeMap = Impossible<String, SerializeError>;
+    type SerializeStruct = Impossible<String, SerializeError>;
+    type SerializeStructVariant = Impossible<String, SerializeError>;
+
+    display_cells! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<String, SerializeError> {
+        CellSerializer::display(String::from_utf8_lossy(v))
+    }
+
+    fn serialize_none(self) -> Result<String, SerializeError> {
+        Ok(String::new())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<String, SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, SerializeError> {
+        Ok(String::new())
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _: &'static str,
+    ) -> Result<String, SerializeError> {
+        Ok(String::new())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<String, SerializeError> {
+        CellSerializer::display(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<String, SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<String, SerializeError>
+        where T: ?Sized + Serialize
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(
+        self,
+        _: Option<usize>,
+    ) -> Result<Self::SerializeSeq, SerializeError> {
+        Err(unsupported("a sequence inside a cell"))
+    }
+
+    fn serialize_tuple(
+        self,
+        _: usize,
+    ) -> Result<Self::SerializeTuple, SerializeError> {
+        Err(unsupported("a tuple inside a cell"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerializeError> {
+        Err(unsupported("a tuple struct inside a cell"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(unsupported("an enum variant inside a cell"))
+    }
+
+    fn serialize_map(
+        self,
+        _: Option<usize>,
+    ) -> Result<Self::SerializeMap, SerializeError> {
+        Err(unsupported("a map inside a cell"))
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, SerializeError> {
+        Err(unsupported("a struct inside a cell"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(unsupported("an enum variant inside a cell"))
+    }
+}
+
+/// Escapes tabs, newlines, carriage returns and the given delimiters, so that
+/// they don't end cells or rows, and backslashes, so that it can be undone.
+fn escape(text: &str, delimiters: &[u8]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_ascii() && delimiters.contains(&(c as u8)) => {
+                escaped.push_str(&format!("\\x{:02x}", c as u8));
+            }
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
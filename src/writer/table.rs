@@ -0,0 +1,100 @@
+use super::{cell_widths, column_maxima, display_columns, Alignment, Cell};
+
+/// Implements the methods that every table writer has, for a table writer
+/// that keeps its `TabWriter` in a field named `tw`.
+macro_rules! table_writer_methods {
+    ($name:ident) => {
+        impl<W: ::std::io::Write> $name<W> {
+            /// Gets a reference to the underlying writer.
+            pub fn get_ref(&self) -> &W {
+                self.tw.get_ref()
+            }
+
+            /// Gets a mutable reference to the underlying writer.
+            ///
+            /// # Warning
+            ///
+            /// It is inadvisable to directly write to the underlying writer,
+            /// since any buffered rows will be written after it.
+            pub fn get_mut(&mut self) -> &mut W {
+                self.tw.get_mut()
+            }
+
+            /// Unwraps this writer, returning the underlying writer.
+            ///
+            /// The buffered table is written before returning the writer. If
+            /// this fails, then an error is returned.
+            #[allow(clippy::result_large_err)]
+            pub fn into_inner(
+                mut self,
+            ) -> Result<W, $crate::writer::IntoInnerError<$name<W>>> {
+                match ::std::io::Write::flush(&mut self) {
+                    Ok(()) => Ok(self.tw.w),
+                    Err(err) => Err($crate::writer::IntoInnerError(self, err)),
+                }
+            }
+        }
+    };
+}
+
+/// Returns the width of every column of a table with the given rows, as
+/// computed by `cell_widths`, where every column is at least `minwidth`
+/// wide.
+///
+/// Unlike with `TabWriter`, the last cell of every row belongs to a column
+/// too, and rows with fewer cells than others are filled up with empty
+/// cells, so that all rows form a single block of columns.
+pub(crate) fn table_widths(
+    rows: &[Vec<String>],
+    minwidth: usize,
+) -> Vec<usize> {
+    let ncols = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let lines: Vec<Vec<Cell>> = rows.iter().map(|row| {
+        let mut line: Vec<Cell> = row.iter()
+            .map(|cell| Cell::from_buf(cell.as_bytes(), 0, cell.len()))
+            .collect();
+        // One more empty cell takes the place of the last cell, which
+        // `cell_widths` leaves out.
+        line.resize(ncols + 1, Cell::new(0));
+        line
+    }).collect();
+    column_maxima(&cell_widths(&lines, minwidth))
+}
+
+/// Returns the cell at index `col` of the given row, padded with spaces to
+/// `width` columns with the given alignment. A missing cell is empty.
+pub(crate) fn pad_cell(
+    row: &[String],
+    col: usize,
+    width: usize,
+    align: Alignment,
+) -> String {
+    let (cell, before, after) = cell_padding(row, col, width, align);
+    format!("{}{}{}", " ".repeat(before), cell, " ".repeat(after))
+}
+
+/// Like `pad_cell`, except the cell is only padded before its text, as is
+/// the last cell of a line written by `TabWriter`.
+pub(crate) fn pad_last_cell(
+    row: &[String],
+    col: usize,
+    width: usize,
+    align: Alignment,
+) -> String {
+    let (cell, before, _) = cell_padding(row, col, width, align);
+    format!("{}{}", " ".repeat(before), cell)
+}
+
+/// Returns the cell at index `col` of the given row, along with the padding
+/// before and after it that makes it `width` columns wide.
+fn cell_padding(
+    row: &[String],
+    col: usize,
+    width: usize,
+    align: Alignment,
+) -> (&str, usize, usize) {
+    let cell = row.get(col).map(|c| &**c).unwrap_or("");
+    let extra = width - display_columns(cell.as_bytes());
+    let (before, after) = align.split(extra);
+    (cell, before, after)
+}